@@ -0,0 +1,4 @@
+(defn sign (x) ((cond (< x 0) -1 (> x 0) 1 :else 0)))
+
+(sign -5)
+[(sign 0) (sign 3)]
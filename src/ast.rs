@@ -1,14 +1,378 @@
+use std::fmt;
+
+use crate::tok::GreedyTokenizer;
+
+/// With the `serde` feature, ASTs serialize tagged by their variant name, like
+/// `{"EvaluateExpr":{"callee":"f","args":[]}}`
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AST {
     NumberExpr(f64),
+    BoolExpr(bool),
+    NilExpr,
+    StringExpr(String),
     VariableExpr(String),
+    KeywordExpr(String),
     EvaluateExpr {
         callee: String,
         args: Vec<AST>,
     },
     FunctionExpr {
         parameters: Vec<String>,
+        /// Gets whatever args are left over after `parameters`, as a list
+        rest_parameter: Option<String>,
         statements: Vec<AST>,
     },
     ListExpr(Vec<AST>),
+    MapExpr(Vec<(AST, AST)>),
+    IfExpr {
+        condition: Box<AST>,
+        then_branch: Box<AST>,
+        else_branch: Option<Box<AST>>,
+    },
+    ApplyExpr {
+        function: Box<AST>,
+        args: Vec<AST>,
+    },
+    LetExpr {
+        bindings: Vec<(String, AST)>,
+        body: Vec<AST>,
+    },
+    DoExpr(Vec<AST>),
+    /// Pairs of a test and the result to give back if it's the first one to pass
+    CondExpr(Vec<(AST, AST)>),
+    /// `(quote x)` or `'x`, which keeps `x` as it is rather than evaluating it
+    QuoteExpr(Box<AST>),
+    /// `(ns app.core)`, naming the namespace the rest of the file belongs to
+    NsExpr(String),
+    /// `some->` and `some->>`, which stop threading as soon as a step gives back nil
+    SomeThreadExpr {
+        initial: Box<AST>,
+        steps: Vec<AST>,
+        thread_last: bool,
+    },
+}
+
+impl AST {
+    /// Renders the AST as source like `Display` does, but breaks up forms that don't fit in
+    /// `width` columns. The head of a broken up form stays on its first line, and the rest of
+    /// its elements go on lines of their own, indented two spaces past its opening bracket.
+    pub fn pretty(&self, width: usize) -> String {
+        let source = self.to_string();
+        let mut pretty = String::new();
+        layout(&layout_tree(&source), 0, width, &mut pretty);
+        pretty
+    }
+}
+
+/// A form or an atom, with the source text it was displayed as.
+enum LayoutNode<'a> {
+    Atom(&'a str),
+    Form {
+        flat: &'a str,
+        open: &'a str,
+        close: &'a str,
+        items: Vec<LayoutNode<'a>>,
+    },
+}
+
+/// Splits up source from `Display`, which always reads back in, into its nested forms.
+fn layout_tree(source: &str) -> LayoutNode<'_> {
+    let mut stack: Vec<(usize, Vec<LayoutNode>)> = vec![(0, vec![])];
+    let tokens = GreedyTokenizer::new(source.as_bytes()).expect("reading from memory can't fail");
+    for token_and_span in tokens {
+        let token_and_span = token_and_span.expect("displayed ASTs always tokenize");
+        // spans end at the start of their last char
+        let from = token_and_span.from.position;
+        let last = token_and_span.to.position;
+        let to = last + source[last..].chars().next().map_or(0, char::len_utf8);
+
+        match &source[from..to] {
+            "(" | "[" | "{" => stack.push((from, vec![])),
+            close @ (")" | "]" | "}") => {
+                let (open, items) = stack.pop().unwrap();
+                stack.last_mut().unwrap().1.push(LayoutNode::Form {
+                    flat: &source[open..to],
+                    open: &source[open..open + 1],
+                    close,
+                    items,
+                });
+            }
+            atom => stack.last_mut().unwrap().1.push(LayoutNode::Atom(atom)),
+        }
+    }
+
+    stack.pop().unwrap().1.pop().unwrap()
+}
+
+fn current_column(out: &str) -> usize {
+    let line_start = out.rfind('\n').map_or(0, |idx| idx + 1);
+    out[line_start..].chars().count()
+}
+
+/// Writes out `node`, which starts at `column`.
+fn layout(node: &LayoutNode, column: usize, width: usize, out: &mut String) {
+    match node {
+        LayoutNode::Atom(text) => out.push_str(text),
+        LayoutNode::Form { flat, .. } if column + flat.chars().count() <= width => {
+            out.push_str(flat)
+        }
+        LayoutNode::Form {
+            open, close, items, ..
+        } => {
+            out.push_str(open);
+
+            // a name at the head of the form keeps its first arg on the same line, like (def x
+            let inline = match items.first() {
+                Some(LayoutNode::Atom(_)) => 2,
+                _ => 1,
+            };
+            for (idx, item) in items.iter().enumerate() {
+                if idx >= inline {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(column + 2));
+                } else if idx > 0 {
+                    out.push(' ');
+                }
+                layout(item, current_column(out), width, out);
+            }
+            out.push_str(close);
+        }
+    }
+}
+
+/// Writes out each item with a space before it, for the parts of a form after its head.
+fn write_spaced<T: fmt::Display>(formatter: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for item in items {
+        write!(formatter, " {}", item)?;
+    }
+    Ok(())
+}
+
+/// Renders the AST back into source that parses into the same AST.
+impl fmt::Display for AST {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AST::NumberExpr(val) if val.is_nan() => write!(formatter, "##NaN"),
+            AST::NumberExpr(val) if val.is_infinite() && *val > 0.0 => write!(formatter, "##Inf"),
+            AST::NumberExpr(val) if val.is_infinite() => write!(formatter, "##-Inf"),
+            AST::NumberExpr(val) => write!(formatter, "{}", val),
+            AST::BoolExpr(val) => write!(formatter, "{}", val),
+            AST::NilExpr => write!(formatter, "nil"),
+            AST::KeywordExpr(name) => write!(formatter, ":{}", name),
+            AST::StringExpr(text) => {
+                write!(formatter, "\"")?;
+                for chr in text.chars() {
+                    match chr {
+                        '\n' => write!(formatter, "\\n")?,
+                        '\t' => write!(formatter, "\\t")?,
+                        '"' => write!(formatter, "\\\"")?,
+                        '\\' => write!(formatter, "\\\\")?,
+                        _ => write!(formatter, "{}", chr)?,
+                    }
+                }
+                write!(formatter, "\"")
+            }
+            AST::VariableExpr(name) => write!(formatter, "{}", name),
+
+            // assignments came from def statements, so they go back to being one
+            AST::EvaluateExpr { callee, args } if callee == "__assign" => {
+                write!(formatter, "(def")?;
+                write_spaced(formatter, args)?;
+                write!(formatter, ")")
+            }
+            AST::EvaluateExpr { callee, args } => {
+                write!(formatter, "({}", callee)?;
+                write_spaced(formatter, args)?;
+                write!(formatter, ")")
+            }
+
+            // the body is a list of statements, so it gets wrapped in parens of its own
+            AST::FunctionExpr {
+                parameters,
+                rest_parameter,
+                statements,
+            } => {
+                write!(formatter, "(fn ({}", parameters.join(" "))?;
+                if let Some(rest_parameter) = rest_parameter {
+                    if !parameters.is_empty() {
+                        write!(formatter, " ")?;
+                    }
+                    write!(formatter, "& {}", rest_parameter)?;
+                }
+                write!(formatter, ") (")?;
+                if let Some((first, rest)) = statements.split_first() {
+                    write!(formatter, "{}", first)?;
+                    write_spaced(formatter, rest)?;
+                }
+                write!(formatter, "))")
+            }
+            AST::ListExpr(items) => {
+                write!(formatter, "[")?;
+                if let Some((first, rest)) = items.split_first() {
+                    write!(formatter, "{}", first)?;
+                    write_spaced(formatter, rest)?;
+                }
+                write!(formatter, "]")
+            }
+            AST::MapExpr(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{} {}", key, value))
+                    .collect::<Vec<String>>();
+                write!(formatter, "{{{}}}", entries.join(" "))
+            }
+            AST::IfExpr {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                write!(formatter, "(if {} {}", condition, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    write!(formatter, " {}", else_branch)?;
+                }
+                write!(formatter, ")")
+            }
+            AST::ApplyExpr { function, args } => {
+                write!(formatter, "({}", function)?;
+                write_spaced(formatter, args)?;
+                write!(formatter, ")")
+            }
+            AST::LetExpr { bindings, body } => {
+                let bindings = bindings
+                    .iter()
+                    .map(|(name, value)| format!("{} {}", name, value))
+                    .collect::<Vec<String>>();
+                write!(formatter, "(let [{}]", bindings.join(" "))?;
+                write_spaced(formatter, body)?;
+                write!(formatter, ")")
+            }
+            AST::DoExpr(body) => {
+                write!(formatter, "(do")?;
+                write_spaced(formatter, body)?;
+                write!(formatter, ")")
+            }
+            AST::CondExpr(clauses) => {
+                write!(formatter, "(cond")?;
+                for (test, result) in clauses {
+                    write!(formatter, " {} {}", test, result)?;
+                }
+                write!(formatter, ")")
+            }
+            AST::QuoteExpr(form) => write!(formatter, "(quote {})", form),
+            AST::NsExpr(name) => write!(formatter, "(ns {})", name),
+            AST::SomeThreadExpr {
+                initial,
+                steps,
+                thread_last,
+            } => {
+                let arrow = if *thread_last { "some->>" } else { "some->" };
+                write!(formatter, "({} {}", arrow, initial)?;
+                write_spaced(formatter, steps)?;
+                write!(formatter, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rstest;
+
+    use rstest::*;
+
+    use super::*;
+    use crate::parser::RecursiveDescentParser;
+
+    fn parse(source: &str) -> AST {
+        *RecursiveDescentParser::from_source(source)
+            .next_expression()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[rstest]
+    // leaf bois
+    #[case("1.5")]
+    #[case("(def x -5)")]
+    #[case("##Inf")]
+    #[case("true")]
+    #[case("nil")]
+    #[case("[:db/id :name]")]
+    #[case("{}")]
+    #[case("{:a 1 \"b\" {:c [2]}}")]
+    #[case("\"a \\\"quoted\\\"\\n\\tstring\\\\\"")]
+    #[case("something")]
+    // form bois
+    #[case("(f)")]
+    #[case("(f 1 (g x \"y\"))")]
+    #[case("(def x 5)")]
+    #[case("(fn () (x))")]
+    #[case("(fn (a b) ((+ a b) (* a b)))")]
+    #[case("[]")]
+    #[case("[1 [x] (f y)]")]
+    #[case("(if true 1)")]
+    #[case("(if (f x) [1] nil)")]
+    #[case("((fn (x) (x)) 5)")]
+    #[case("((fn () (42)))")]
+    #[case("((f))")]
+    #[case("(let [] 1)")]
+    #[case("(let [x 1 y (f x)] (+ x y) y)")]
+    #[case("(fn (x & rest) (rest))")]
+    #[case("(fn (& rest) (rest))")]
+    #[case("(do)")]
+    #[case("(do (def x 1) (f x))")]
+    #[case("(cond)")]
+    #[case("(cond (< x 0) -1 (> x 0) 1 :else 0)")]
+    #[case("(ns app.core)")]
+    #[case("(quote x)")]
+    #[case("(f (quote [1 (g y)]))")]
+    #[case("(some-> x (f 1) g)")]
+    #[case("(some->> x (f 1) ((fn (a b) (a)) 2))")]
+    fn it_displays_asts_as_the_source_they_came_from(#[case] source: &str) {
+        let ast = parse(source);
+        assert_eq!(ast.to_string(), source);
+        assert_eq!(parse(&ast.to_string()), ast);
+    }
+
+    #[test]
+    fn it_pretty_prints_forms_that_dont_fit_over_several_lines() {
+        let ast = parse("(defn greet (name) ((println (str \"hello \" name)) (count name)))");
+        assert_eq!(ast.pretty(80), ast.to_string());
+        assert_eq!(
+            ast.pretty(40),
+            "(def greet
+  (fn (name)
+    ((println (str \"hello \" name))
+      (count name))))"
+        );
+        assert_eq!(parse(&ast.pretty(40)), ast);
+
+        // atoms never get broken up, even when they're too wide on their own
+        let ast = parse("[\"a long string\" 2 {:k ##-Inf}]");
+        assert_eq!(ast.pretty(5), "[\"a long string\" 2\n  {:k ##-Inf}]");
+        assert_eq!(parse(&ast.pretty(5)), ast);
+    }
+
+    #[test]
+    fn it_displays_expanded_forms_in_their_expanded_shape() {
+        assert_eq!(
+            parse("(defn f (x) (x))").to_string(),
+            "(def f (fn (x) (x)))"
+        );
+        assert_eq!(parse("(-> x (f 1) g)").to_string(), "(g (f x 1))");
+        assert_eq!(parse("007").to_string(), "7");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_to_json_and_back() {
+        let ast = parse("(f 1 \"two\" nil)");
+        let json = serde_json::to_string(&ast).unwrap();
+        assert_eq!(
+            json,
+            r#"{"EvaluateExpr":{"callee":"f","args":[{"NumberExpr":1.0},{"StringExpr":"two"},"NilExpr"]}}"#
+        );
+        assert_eq!(serde_json::from_str::<AST>(&json).unwrap(), ast);
+    }
 }
@@ -0,0 +1,1255 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::ast::AST;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+    Nil,
+    Keyword(String),
+    List(List),
+    Builtin(Builtin),
+    Closure(Rc<Closure>),
+}
+
+impl Value {
+    /// Everything but `nil` and `false` counts as true when branching.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+}
+
+/// A list's items, which never change once it's built. They sit behind an Rc so copies of the
+/// list, and what's left of it after `rest`, share them instead of copying every item.
+#[derive(Clone, Default)]
+pub struct List {
+    items: Rc<Vec<Value>>,
+    // where this list starts in `items`, since `rest` just moves past the first one
+    start: usize,
+}
+
+impl List {
+    /// Everything after the first item, which is empty for an empty list.
+    pub fn rest(&self) -> List {
+        List {
+            items: self.items.clone(),
+            start: (self.start + 1).min(self.items.len()),
+        }
+    }
+}
+
+impl Deref for List {
+    type Target = [Value];
+
+    fn deref(&self) -> &[Value] {
+        &self.items[self.start..]
+    }
+}
+
+impl From<Vec<Value>> for List {
+    fn from(items: Vec<Value>) -> Self {
+        List {
+            items: Rc::new(items),
+            start: 0,
+        }
+    }
+}
+
+impl FromIterator<Value> for List {
+    fn from_iter<I: IntoIterator<Item = Value>>(items: I) -> Self {
+        List::from(items.into_iter().collect::<Vec<Value>>())
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// lists are equal when their items are, wherever those happen to be stored
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+/// Renders values the way they'd be written in source, where they can be. Functions can't be,
+/// so they show up as `#<fn>`.
+impl fmt::Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // numbers and strings print just like their literals do
+            Value::Number(val) => write!(formatter, "{}", AST::NumberExpr(*val)),
+            Value::Str(text) => write!(formatter, "{}", AST::StringExpr(text.clone())),
+            Value::Bool(val) => write!(formatter, "{}", val),
+            Value::Nil => write!(formatter, "nil"),
+            Value::Keyword(name) => write!(formatter, ":{}", name),
+            Value::List(items) => {
+                let items = items.iter().map(Value::to_string).collect::<Vec<String>>();
+                write!(formatter, "[{}]", items.join(" "))
+            }
+            Value::Builtin(builtin) => write!(formatter, "#<fn {}>", builtin.name),
+            Value::Closure(_) => write!(formatter, "#<fn>"),
+        }
+    }
+}
+
+pub type BuiltinFn = dyn Fn(&mut Evaluator, Vec<Value>) -> Result<Value, EvalError>;
+
+/// A function implemented in rust, called with its already-evaluated args
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: String,
+    func: Rc<BuiltinFn>,
+}
+
+impl Builtin {
+    pub fn new<F>(name: &str, func: F) -> Self
+    where
+        F: Fn(&mut Evaluator, Vec<Value>) -> Result<Value, EvalError> + 'static,
+    {
+        Builtin {
+            name: String::from(name),
+            func: Rc::new(func),
+        }
+    }
+
+    pub fn call(&self, evaluator: &mut Evaluator, args: Vec<Value>) -> Result<Value, EvalError> {
+        (self.func)(evaluator, args)
+    }
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Builtin({})", self.name)
+    }
+}
+
+// functions can't be compared by what they do, so a builtin is only equal to itself
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+/// A function defined in lisp, along with the environment it was defined in
+#[derive(Clone)]
+pub struct Closure {
+    pub parameters: Vec<String>,
+    pub rest_parameter: Option<String>,
+    pub body: Vec<AST>,
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the environment can hold this very closure, so it's left out
+        write!(f, "Closure({:?})", self.parameters)
+    }
+}
+
+// a closure is only equal to itself, and values share one behind an Rc, so that's when they're
+// at the same address
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+    UndefinedVariable(String),
+    NotCallable(String),
+    WrongNumberOfArgs {
+        callee: String,
+        expected: String,
+        found: usize,
+    },
+    TypeMismatch {
+        callee: String,
+        expected: String,
+        found: Value,
+    },
+    DivisionByZero,
+    /// Calls nested more than `depth` deep, which would've overflowed the rust stack
+    StackOverflow {
+        depth: usize,
+    },
+    UnexpectedExpression(AST),
+    NotYetSupported(AST),
+}
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    bindings: HashMap<String, Value>,
+    parent: Option<Rc<RefCell<Environment>>>,
+    /// Set by an `ns` form
+    namespace: Option<String>,
+}
+
+impl Environment {
+    /// A new scope whose lookups fall back on `parent`.
+    pub fn child(parent: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            bindings: HashMap::new(),
+            parent: Some(parent),
+            namespace: None,
+        }
+    }
+
+    /// The namespace of the innermost scope that declared one.
+    pub fn namespace(&self) -> Option<String> {
+        match &self.namespace {
+            Some(namespace) => Some(namespace.clone()),
+            None => self.parent.as_ref()?.borrow().namespace(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.bindings.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.borrow().get(name),
+        }
+    }
+
+    pub fn set(&mut self, name: String, value: Value) {
+        self.bindings.insert(name, value);
+    }
+}
+
+/// How deeply closure calls can nest by default. Each call recurses through `eval` and `apply`, and takes up
+/// a few KB of stack in unoptimized builds, so calls this deep fit on the 8MB stack rust's main
+/// thread gets. Threads spawned with the default 2MB only fit this many in optimized builds.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+#[derive(Debug)]
+pub struct Evaluator {
+    environment: Rc<RefCell<Environment>>,
+    /// How many closure calls are running right now
+    call_depth: usize,
+    max_call_depth: usize,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator::new()
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        let mut environment = Environment::default();
+        let builtins = arithmetic_builtins()
+            .into_iter()
+            .chain(comparison_builtins())
+            .chain(list_builtins())
+            .chain(function_builtins());
+        for builtin in builtins {
+            environment.set(builtin.name.clone(), Value::Builtin(builtin));
+        }
+
+        Evaluator {
+            environment: Rc::new(RefCell::new(environment)),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+
+    /// Caps how deeply closure calls can nest. Each call recurses on the rust stack, so anything
+    /// deeper fails with `StackOverflow` instead. Defaults to `DEFAULT_MAX_CALL_DEPTH`.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    /// The namespace the last `ns` form declared, if there was one.
+    pub fn namespace(&self) -> Option<String> {
+        self.environment.borrow().namespace()
+    }
+
+    // every call and nested form recurses through here, so each kind of form gets evaluated in a
+    // function of its own and this one's stack frame stays small
+    pub fn eval(&mut self, ast: &AST) -> Result<Value, EvalError> {
+        match ast {
+            AST::NumberExpr(val) => Ok(Value::Number(*val)),
+            AST::BoolExpr(val) => Ok(Value::Bool(*val)),
+            AST::StringExpr(text) => Ok(Value::Str(text.clone())),
+            AST::NilExpr => Ok(Value::Nil),
+            AST::KeywordExpr(name) => Ok(Value::Keyword(name.clone())),
+            AST::VariableExpr(name) => self.lookup(name),
+
+            // def statements get parsed into assignments
+            AST::EvaluateExpr { callee, args } if callee == "__assign" => {
+                self.eval_assignment(ast, args)
+            }
+            AST::EvaluateExpr { callee, args } => self.eval_call(callee, args),
+            AST::ApplyExpr { function, args } => self.eval_apply(function, args),
+
+            AST::ListExpr(items) => self.eval_list(items),
+            AST::FunctionExpr {
+                parameters,
+                rest_parameter,
+                statements,
+            } => Ok(self.closure(parameters, rest_parameter, statements)),
+
+            AST::NsExpr(name) => {
+                self.environment.borrow_mut().namespace = Some(name.clone());
+                Ok(Value::Nil)
+            }
+
+            AST::DoExpr(body) => self.eval_body(body),
+            AST::CondExpr(clauses) => self.eval_cond(clauses),
+            AST::SomeThreadExpr {
+                initial,
+                steps,
+                thread_last,
+            } => self.eval_some_thread(initial, steps, *thread_last),
+
+            _ => Err(EvalError::NotYetSupported(ast.clone())),
+        }
+    }
+
+    fn eval_assignment(&mut self, ast: &AST, args: &[AST]) -> Result<Value, EvalError> {
+        match args {
+            [AST::VariableExpr(name), value] => {
+                let value = self.eval(value)?;
+                self.environment
+                    .borrow_mut()
+                    .set(name.clone(), value.clone());
+                Ok(value)
+            }
+            _ => Err(EvalError::UnexpectedExpression(ast.clone())),
+        }
+    }
+
+    fn eval_call(&mut self, callee: &str, args: &[AST]) -> Result<Value, EvalError> {
+        let function = self.lookup(callee)?;
+        let args = self.eval_args(args)?;
+        self.apply(callee, &function, args)
+    }
+
+    fn eval_apply(&mut self, function: &AST, args: &[AST]) -> Result<Value, EvalError> {
+        let function = self.eval(function)?;
+        let args = self.eval_args(args)?;
+        self.apply("fn", &function, args)
+    }
+
+    fn eval_list(&mut self, items: &[AST]) -> Result<Value, EvalError> {
+        self.eval_args(items).map(|items| Value::List(items.into()))
+    }
+
+    fn closure(
+        &self,
+        parameters: &[String],
+        rest_parameter: &Option<String>,
+        statements: &[AST],
+    ) -> Value {
+        Value::Closure(Rc::new(Closure {
+            parameters: parameters.to_vec(),
+            rest_parameter: rest_parameter.clone(),
+            body: statements.to_vec(),
+            environment: self.environment.clone(),
+        }))
+    }
+
+    /// Evaluates statements in order, giving back the last one's value.
+    fn eval_body(&mut self, statements: &[AST]) -> Result<Value, EvalError> {
+        let mut result = Value::Nil;
+        for statement in statements {
+            result = self.eval(statement)?;
+        }
+        Ok(result)
+    }
+
+    fn eval_cond(&mut self, clauses: &[(AST, AST)]) -> Result<Value, EvalError> {
+        for (test, result) in clauses {
+            if self.eval(test)?.is_truthy() {
+                return self.eval(result);
+            }
+        }
+        Ok(Value::Nil)
+    }
+
+    fn eval_some_thread(
+        &mut self,
+        initial: &AST,
+        steps: &[AST],
+        thread_last: bool,
+    ) -> Result<Value, EvalError> {
+        let mut threaded = self.eval(initial)?;
+        for step in steps {
+            if threaded == Value::Nil {
+                break;
+            }
+
+            let (name, function, mut args) = self.eval_thread_step(step)?;
+            if thread_last {
+                args.push(threaded);
+            } else {
+                args.insert(0, threaded);
+            }
+            threaded = self.apply(name, &function, args)?;
+        }
+
+        Ok(threaded)
+    }
+
+    /// The name of the function a threading step calls, the function, and the args it gets along
+    /// with the threaded value.
+    fn eval_thread_step<'a>(
+        &mut self,
+        step: &'a AST,
+    ) -> Result<(&'a str, Value, Vec<Value>), EvalError> {
+        match step {
+            AST::VariableExpr(callee) => Ok((callee.as_str(), self.lookup(callee)?, vec![])),
+            AST::EvaluateExpr { callee, args } => {
+                Ok((callee.as_str(), self.lookup(callee)?, self.eval_args(args)?))
+            }
+            AST::ApplyExpr { function, args } => {
+                Ok(("fn", self.eval(function)?, self.eval_args(args)?))
+            }
+            _ => Err(EvalError::UnexpectedExpression(step.clone())),
+        }
+    }
+
+    /// Calls a builtin or closure with already-evaluated args. `name` is only used for errors.
+    pub fn apply(
+        &mut self,
+        name: &str,
+        function: &Value,
+        args: Vec<Value>,
+    ) -> Result<Value, EvalError> {
+        match function {
+            Value::Builtin(builtin) => builtin.call(self, args),
+            Value::Closure(closure) => self.call_closure(name, closure, args),
+            _ => Err(EvalError::NotCallable(String::from(name))),
+        }
+    }
+
+    fn call_closure(
+        &mut self,
+        name: &str,
+        closure: &Closure,
+        args: Vec<Value>,
+    ) -> Result<Value, EvalError> {
+        let fixed = closure.parameters.len();
+        match closure.rest_parameter {
+            None if args.len() != fixed => {
+                return Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from(name),
+                    expected: fixed.to_string(),
+                    found: args.len(),
+                })
+            }
+            Some(_) if args.len() < fixed => {
+                return Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from(name),
+                    expected: format!("at least {}", fixed),
+                    found: args.len(),
+                })
+            }
+            _ => {}
+        }
+        if self.call_depth >= self.max_call_depth {
+            return Err(EvalError::StackOverflow {
+                depth: self.max_call_depth,
+            });
+        }
+
+        // the args get bound in a scope of their own, on top of the one the closure captured
+        let mut scope = Environment::child(closure.environment.clone());
+        let mut args = args.into_iter();
+        for (parameter, arg) in closure.parameters.iter().zip(args.by_ref()) {
+            scope.set(parameter.clone(), arg);
+        }
+        if let Some(rest_parameter) = &closure.rest_parameter {
+            scope.set(rest_parameter.clone(), Value::List(args.collect()));
+        }
+
+        let caller_environment =
+            std::mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        self.call_depth += 1;
+        let result = self.eval_body(&closure.body);
+        self.call_depth -= 1;
+        self.environment = caller_environment;
+
+        result
+    }
+
+    fn lookup(&self, name: &str) -> Result<Value, EvalError> {
+        self.environment
+            .borrow()
+            .get(name)
+            .ok_or_else(|| EvalError::UndefinedVariable(String::from(name)))
+    }
+
+    fn eval_args(&mut self, args: &[AST]) -> Result<Vec<Value>, EvalError> {
+        args.iter().map(|arg| self.eval(arg)).collect()
+    }
+}
+
+fn numbers(callee: &str, args: Vec<Value>) -> Result<Vec<f64>, EvalError> {
+    args.into_iter()
+        .map(|arg| match arg {
+            Value::Number(val) => Ok(val),
+            other => Err(EvalError::TypeMismatch {
+                callee: String::from(callee),
+                expected: String::from("number"),
+                found: other,
+            }),
+        })
+        .collect()
+}
+
+fn at_least_one(callee: &str, nums: &[f64]) -> Result<(), EvalError> {
+    if nums.is_empty() {
+        return Err(EvalError::WrongNumberOfArgs {
+            callee: String::from(callee),
+            expected: String::from("at least 1"),
+            found: 0,
+        });
+    }
+    Ok(())
+}
+
+fn arithmetic_builtins() -> Vec<Builtin> {
+    vec![
+        Builtin::new("+", |_, args| {
+            Ok(Value::Number(numbers("+", args)?.into_iter().sum()))
+        }),
+        Builtin::new("*", |_, args| {
+            Ok(Value::Number(numbers("*", args)?.into_iter().product()))
+        }),
+        Builtin::new("-", |_, args| {
+            let nums = numbers("-", args)?;
+            at_least_one("-", &nums)?;
+
+            // a single arg gets negated
+            if nums.len() == 1 {
+                return Ok(Value::Number(-nums[0]));
+            }
+            Ok(Value::Number(
+                nums[1..].iter().fold(nums[0], |acc, num| acc - num),
+            ))
+        }),
+        Builtin::new("/", |_, args| {
+            let nums = numbers("/", args)?;
+            at_least_one("/", &nums)?;
+
+            // a single arg gets inverted
+            let (first, rest) = if nums.len() == 1 {
+                (1.0, &nums[..])
+            } else {
+                (nums[0], &nums[1..])
+            };
+            rest.iter()
+                .try_fold(first, |acc, num| {
+                    if *num == 0.0 {
+                        Err(EvalError::DivisionByZero)
+                    } else {
+                        Ok(acc / num)
+                    }
+                })
+                .map(Value::Number)
+        }),
+    ]
+}
+
+fn comparison_builtins() -> Vec<Builtin> {
+    vec![
+        Builtin::new("=", |_, args| all_equal("=", &args).map(Value::Bool)),
+        Builtin::new("not=", |_, args| {
+            all_equal("not=", &args).map(|equal| Value::Bool(!equal))
+        }),
+        ordering("<", |left, right| left < right),
+        ordering(">", |left, right| left > right),
+        ordering("<=", |left, right| left <= right),
+        ordering(">=", |left, right| left >= right),
+    ]
+}
+
+fn all_equal(callee: &str, args: &[Value]) -> Result<bool, EvalError> {
+    if args.is_empty() {
+        return Err(EvalError::WrongNumberOfArgs {
+            callee: String::from(callee),
+            expected: String::from("at least 1"),
+            found: 0,
+        });
+    }
+    Ok(args.windows(2).all(|pair| pair[0] == pair[1]))
+}
+
+/// A builtin checking that each number is in order with the one after it, as in `(< 1 2 3)`
+fn ordering(name: &'static str, in_order: fn(f64, f64) -> bool) -> Builtin {
+    Builtin::new(name, move |_, args| {
+        let nums = numbers(name, args)?;
+        at_least_one(name, &nums)?;
+        Ok(Value::Bool(
+            nums.windows(2).all(|pair| in_order(pair[0], pair[1])),
+        ))
+    })
+}
+
+fn list_builtins() -> Vec<Builtin> {
+    vec![
+        Builtin::new("list", |_, args| Ok(Value::List(args.into()))),
+        Builtin::new("first", |_, args| {
+            let items = single_list("first", args)?;
+            Ok(items.first().cloned().unwrap_or(Value::Nil))
+        }),
+        Builtin::new("rest", |_, args| {
+            let items = single_list("rest", args)?;
+            Ok(Value::List(items.rest()))
+        }),
+        Builtin::new("cons", |_, args| match &args[..] {
+            // there's no room in front of shared items for a new first one, so cons still copies them
+            [item, Value::List(items)] => Ok(Value::List(
+                std::iter::once(item.clone())
+                    .chain(items.iter().cloned())
+                    .collect(),
+            )),
+            [_, other] => Err(EvalError::TypeMismatch {
+                callee: String::from("cons"),
+                expected: String::from("list"),
+                found: other.clone(),
+            }),
+            _ => Err(EvalError::WrongNumberOfArgs {
+                callee: String::from("cons"),
+                expected: String::from("2"),
+                found: args.len(),
+            }),
+        }),
+    ]
+}
+
+/// The items of the one list that `callee` takes as its only arg
+fn single_list(callee: &str, mut args: Vec<Value>) -> Result<List, EvalError> {
+    if args.len() != 1 {
+        return Err(EvalError::WrongNumberOfArgs {
+            callee: String::from(callee),
+            expected: String::from("1"),
+            found: args.len(),
+        });
+    }
+    match args.pop().unwrap() {
+        Value::List(items) => Ok(items),
+        other => Err(EvalError::TypeMismatch {
+            callee: String::from(callee),
+            expected: String::from("list"),
+            found: other,
+        }),
+    }
+}
+
+fn function_builtins() -> Vec<Builtin> {
+    vec![Builtin::new("fnil", |_, args| match &args[..] {
+        [func @ (Value::Builtin(_) | Value::Closure(_)), default] => {
+            let func = func.clone();
+            let default = default.clone();
+            let func_name = match &func {
+                Value::Builtin(builtin) => builtin.name.clone(),
+                _ => String::from("fn"),
+            };
+
+            // swap out a nil first arg for the default before calling through
+            let name = format!("fnil({})", func_name);
+            Ok(Value::Builtin(Builtin::new(
+                &name,
+                move |evaluator, mut args| {
+                    if let Some(first @ Value::Nil) = args.first_mut() {
+                        *first = default.clone();
+                    }
+                    evaluator.apply(&func_name, &func, args)
+                },
+            )))
+        }
+        [other, _] => Err(EvalError::TypeMismatch {
+            callee: String::from("fnil"),
+            expected: String::from("function"),
+            found: other.clone(),
+        }),
+        _ => Err(EvalError::WrongNumberOfArgs {
+            callee: String::from("fnil"),
+            expected: String::from("2"),
+            found: args.len(),
+        }),
+    })]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RecursiveDescentParser;
+    use crate::test_support::assert_evals_to;
+
+    fn eval_all(source: &str) -> Vec<Result<Value, EvalError>> {
+        let mut parser = RecursiveDescentParser::from_source(source);
+        let mut evaluator = Evaluator::new();
+
+        parser
+            .parse_all()
+            .unwrap()
+            .iter()
+            .map(|ast| evaluator.eval(ast))
+            .collect()
+    }
+
+    #[test]
+    fn it_evaluates_literals() {
+        assert_eq!(
+            eval_all("1.5 true false \"hi\" nil"),
+            vec![
+                Ok(Value::Number(1.5)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Str(String::from("hi"))),
+                Ok(Value::Nil),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_sets_the_current_namespace() {
+        let mut evaluator = Evaluator::new();
+        assert_eq!(evaluator.namespace(), None);
+
+        let mut parser = RecursiveDescentParser::from_source("(ns app.core) (def x 1)");
+        for ast in parser.parse_all().unwrap() {
+            evaluator.eval(&ast).unwrap();
+        }
+        assert_eq!(evaluator.namespace(), Some(String::from("app.core")));
+    }
+
+    #[test]
+    fn it_displays_values_as_source() {
+        let displayed = eval_all(
+            "5 -2.5 ##Inf true nil \"a \\\"b\\\"\" :k [1 [nil \"x\"] false] [] + (fn (x) (x))",
+        )
+        .into_iter()
+        .map(|value| value.unwrap().to_string())
+        .collect::<Vec<String>>();
+        assert_eq!(
+            displayed,
+            vec![
+                "5",
+                "-2.5",
+                "##Inf",
+                "true",
+                "nil",
+                "\"a \\\"b\\\"\"",
+                ":k",
+                "[1 [nil \"x\"] false]",
+                "[]",
+                "#<fn +>",
+                "#<fn>",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_looks_up_defined_variables() {
+        assert_eq!(
+            eval_all("(def x 5) x"),
+            vec![Ok(Value::Number(5.0)), Ok(Value::Number(5.0))]
+        );
+
+        // redefining a variable replaces it
+        assert_eq!(
+            eval_all("(def x 5) (def x true) x"),
+            vec![
+                Ok(Value::Number(5.0)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(true))
+            ]
+        );
+
+        // assignments can refer to other variables
+        assert_eq!(
+            eval_all("(def x 5) (def y x) y"),
+            vec![
+                Ok(Value::Number(5.0)),
+                Ok(Value::Number(5.0)),
+                Ok(Value::Number(5.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_undefined_variables() {
+        assert_eq!(
+            eval_all("x (def y x)"),
+            vec![
+                Err(EvalError::UndefinedVariable(String::from("x"))),
+                Err(EvalError::UndefinedVariable(String::from("x")))
+            ]
+        );
+    }
+
+    #[test]
+    fn it_evaluates_arithmetic() {
+        assert_eq!(
+            eval_all("(+ 1 2 3) (- 10 4 1) (* 2 3 4) (/ 12 3 2)"),
+            vec![
+                Ok(Value::Number(6.0)),
+                Ok(Value::Number(5.0)),
+                Ok(Value::Number(24.0)),
+                Ok(Value::Number(2.0)),
+            ]
+        );
+
+        // identities, negation and inversion
+        assert_eq!(
+            eval_all("(+) (*) (- 3) (/ 4)"),
+            vec![
+                Ok(Value::Number(0.0)),
+                Ok(Value::Number(1.0)),
+                Ok(Value::Number(-3.0)),
+                Ok(Value::Number(0.25)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_evaluates_comparisons() {
+        assert_eq!(
+            eval_all("(< 1 2) (< 1 3 2) (> 3 2 1) (<= 1 1 2) (>= 2 3) (< 5)"),
+            vec![
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(true)),
+            ]
+        );
+
+        // equality is structural, and doesn't mind what types it's given
+        assert_eq!(
+            eval_all("(= 3 3) (= 3 3 4) (= true true) (= 1 true) (= [1 2] [1 2]) (not= 1 2)"),
+            vec![
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(true)),
+            ]
+        );
+
+        assert_eq!(
+            eval_all("(< 1 true) (=)"),
+            vec![
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("<"),
+                    expected: String::from("number"),
+                    found: Value::Bool(true),
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("="),
+                    expected: String::from("at least 1"),
+                    found: 0,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_evaluates_nested_arithmetic() {
+        assert_eq!(
+            eval_all("(+ 1 (* 2 3)) (def x 4) (/ (- x 1) (+ x 2))"),
+            vec![
+                Ok(Value::Number(7.0)),
+                Ok(Value::Number(4.0)),
+                Ok(Value::Number(0.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_bad_arithmetic() {
+        assert_eq!(
+            eval_all("(/ 1 0) (/ 0) (+ 1 true) (-)"),
+            vec![
+                Err(EvalError::DivisionByZero),
+                Err(EvalError::DivisionByZero),
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("+"),
+                    expected: String::from("number"),
+                    found: Value::Bool(true),
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("-"),
+                    expected: String::from("at least 1"),
+                    found: 0,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_substitutes_nil_first_args_with_fnil() {
+        assert_eq!(
+            eval_all("(def add (fnil + 10)) (add nil 5) (add 1 5) (add 1 nil)")[1..],
+            [
+                Ok(Value::Number(15.0)),
+                Ok(Value::Number(6.0)),
+                // only the first arg gets a default
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("+"),
+                    expected: String::from("number"),
+                    found: Value::Nil,
+                }),
+            ]
+        );
+
+        assert_eq!(
+            eval_all("(fnil 1 10) (fnil +)"),
+            vec![
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("fnil"),
+                    expected: String::from("function"),
+                    found: Value::Number(1.0),
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("fnil"),
+                    expected: String::from("2"),
+                    found: 1,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_calls_functions() {
+        assert_evals_to("((fn (x) ((* x x))) 5)", Value::Number(25.0));
+        assert_evals_to(
+            "(def square (fn (x) ((* x x)))) (square 4)",
+            Value::Number(16.0),
+        );
+        assert_evals_to(
+            "(defn add3 (a b c) ((+ a b) (+ a b c))) (add3 1 2 3)",
+            Value::Number(6.0),
+        );
+
+        // parens around a form call what it gives back, even without any args
+        assert_evals_to("((fn () (42)))", Value::Number(42.0));
+        assert_evals_to("(defn mk () ((fn () (42)))) ((mk))", Value::Number(42.0));
+    }
+
+    #[test]
+    fn it_compares_values_structurally() {
+        assert_evals_to(
+            "(cons :a (list [1 \"b\"] nil))",
+            Value::List(
+                vec![
+                    Value::Keyword(String::from("a")),
+                    Value::List(vec![Value::Number(1.0), Value::Str(String::from("b"))].into()),
+                    Value::Nil,
+                ]
+                .into(),
+            ),
+        );
+
+        // functions are only equal to themselves, however alike they look
+        assert_eq!(
+            eval_all(
+                "(defn f (x) (x)) (= f f) (= + +) (= (fn (x) (x)) (fn (x) (x))) \
+                 (= (fnil + 1) (fnil + 2)) (= (fnil + 1) (fnil + 1))"
+            )[1..],
+            [
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(true)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(false)),
+                Ok(Value::Bool(false))
+            ]
+        );
+    }
+
+    #[test]
+    fn it_calls_functions_in_their_own_scope() {
+        // parameters shadow variables outside, without replacing them
+        assert_eq!(
+            eval_all("(def x 1) (defn inc (x) ((+ x 1))) (inc 10) x")[2..],
+            [Ok(Value::Number(11.0)), Ok(Value::Number(1.0))]
+        );
+
+        // closures hang onto the variables around them
+        assert_eq!(
+            eval_all(
+                "(defn make_adder (n) ((fn (x) ((+ x n))))) (def add2 (make_adder 2)) (add2 3) ((make_adder 10) 3)"
+            )[2..],
+            [Ok(Value::Number(5.0)), Ok(Value::Number(13.0))]
+        );
+
+        // and see variables defined after them
+        assert_eq!(
+            eval_all("(defn get_y () (y)) (def y 7) (get_y)")[2..],
+            [Ok(Value::Number(7.0))]
+        );
+
+        // builtins can wrap them too
+        assert_eq!(
+            eval_all(
+                "(defn sub (a b) ((- a b))) (def sub_from_10 (fnil sub 10)) (sub_from_10 nil 3)"
+            )[2..],
+            [Ok(Value::Number(7.0))]
+        );
+    }
+
+    #[test]
+    fn it_evaluates_do_blocks_in_order() {
+        assert_eq!(
+            eval_all("(do (def x 2) (def y (* x 3)) (+ x y)) y (do)"),
+            vec![
+                Ok(Value::Number(8.0)),
+                Ok(Value::Number(6.0)),
+                Ok(Value::Nil)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_builds_and_takes_apart_lists() {
+        let list = |items: &[f64]| Value::List(items.iter().map(|n| Value::Number(*n)).collect());
+        assert_eq!(
+            eval_all(
+                "(list 1 2 3) (list) (first (list 1 2 3)) (rest (list 1 2 3)) (cons 0 (list 1 2)) \
+                 (first [(+ 1 1)])"
+            ),
+            vec![
+                Ok(list(&[1.0, 2.0, 3.0])),
+                Ok(list(&[])),
+                Ok(Value::Number(1.0)),
+                Ok(list(&[2.0, 3.0])),
+                Ok(list(&[0.0, 1.0, 2.0])),
+                Ok(Value::Number(2.0)),
+            ]
+        );
+
+        // empty lists have no first, and nothing left after it
+        assert_eq!(
+            eval_all("(first (list)) (rest (list)) (rest [1]) (cons 1 [])"),
+            vec![
+                Ok(Value::Nil),
+                Ok(list(&[])),
+                Ok(list(&[])),
+                Ok(list(&[1.0]))
+            ]
+        );
+    }
+
+    #[test]
+    fn it_shares_list_items_instead_of_copying_them() {
+        let items: List = (0..1000).map(|n| Value::Number(n as f64)).collect();
+
+        // what's left after rest points into the same items, however long the list is
+        let rest = items.rest();
+        assert_eq!(rest.len(), 999);
+        assert!(std::ptr::eq(&rest[0], &items[1]));
+        assert!(std::ptr::eq(&rest.rest()[0], &items[2]));
+
+        // and building on a list leaves every other reference to it as it was
+        assert_eq!(
+            eval_all("(def xs (list 1 2 3)) (def ys (rest xs)) (cons 0 ys) xs ys")[2..],
+            [
+                Ok(Value::List(
+                    vec![Value::Number(0.0), Value::Number(2.0), Value::Number(3.0)].into()
+                )),
+                Ok(Value::List(
+                    vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into()
+                )),
+                Ok(Value::List(
+                    vec![Value::Number(2.0), Value::Number(3.0)].into()
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_list_functions_on_non_lists() {
+        assert_eq!(
+            eval_all("(first 1) (rest nil) (cons 1 \"abc\") (first) (cons 1)"),
+            vec![
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("first"),
+                    expected: String::from("list"),
+                    found: Value::Number(1.0),
+                }),
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("rest"),
+                    expected: String::from("list"),
+                    found: Value::Nil,
+                }),
+                Err(EvalError::TypeMismatch {
+                    callee: String::from("cons"),
+                    expected: String::from("list"),
+                    found: Value::Str(String::from("abc")),
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("first"),
+                    expected: String::from("1"),
+                    found: 0,
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("cons"),
+                    expected: String::from("2"),
+                    found: 1,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_evaluates_the_first_cond_clause_that_passes() {
+        assert_eq!(
+            eval_all(
+                "(defn sign (x) ((cond (< x 0) -1 (> x 0) 1 :else 0))) \
+                 (sign -5) (sign 5) (sign 0) (cond false 1 nil 2)"
+            )[1..],
+            [
+                Ok(Value::Number(-1.0)),
+                Ok(Value::Number(1.0)),
+                Ok(Value::Number(0.0)),
+                Ok(Value::Nil)
+            ]
+        );
+
+        // results after the passing test never get evaluated
+        assert_eq!(
+            eval_all("(cond true 1 true undefined)"),
+            vec![Ok(Value::Number(1.0))]
+        );
+    }
+
+    #[test]
+    fn it_threads_values_until_a_step_gives_back_nil() {
+        assert_eq!(
+            eval_all(
+                "(defn nothing (x) (nil)) (some-> 1 (+ 2) nothing (+ 3)) (some-> nil (+ 1)) \
+                 (some-> 1 (+ 2) (* 3)) (some->> 2 (- 10) (/ 16))"
+            )[1..],
+            [
+                // without stopping at the nil, (+ nil 3) would be a type mismatch
+                Ok(Value::Nil),
+                Ok(Value::Nil),
+                Ok(Value::Number(9.0)),
+                Ok(Value::Number(2.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_collects_leftover_args_into_rest_parameters() {
+        assert_eq!(
+            eval_all(
+                "(defn f (x & rest) (rest)) (f 1 2 (+ 1 2)) (f 1) ((fn (& all) (all)) 1) [1 (* 2 3)] (f)"
+            )[1..],
+            [
+                Ok(Value::List(vec![Value::Number(2.0), Value::Number(3.0)].into())),
+                Ok(Value::List(vec![].into())),
+                Ok(Value::List(vec![Value::Number(1.0)].into())),
+                Ok(Value::List(vec![Value::Number(1.0), Value::Number(6.0)].into())),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("f"),
+                    expected: String::from("at least 1"),
+                    found: 0,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_wrong_number_of_args_to_functions() {
+        assert_eq!(
+            eval_all("(defn square (x) ((* x x))) (square 1 2) ((fn (x y) ((+ x y))) 1)")[1..],
+            [
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("square"),
+                    expected: String::from("1"),
+                    found: 2,
+                }),
+                Err(EvalError::WrongNumberOfArgs {
+                    callee: String::from("fn"),
+                    expected: String::from("2"),
+                    found: 1,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_unbounded_recursion() {
+        // tests run on 2MB threads, so this gets a stack the size of the main thread's
+        let recurse = || {
+            let results =
+                eval_all("(defn f (x) ((f x))) (f 1) (defn g (x) (some-> x g)) (g 1) (+ 1 2)");
+            let overflow = Err(EvalError::StackOverflow {
+                depth: DEFAULT_MAX_CALL_DEPTH,
+            });
+            assert_eq!(results[1], overflow);
+            assert_eq!(results[3], overflow);
+            // and the evaluator's still good to use after
+            assert_eq!(results[4], Ok(Value::Number(3.0)));
+        };
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(recurse)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn it_limits_call_depth_to_what_its_configured_with() {
+        let mut parser = RecursiveDescentParser::from_source(
+            "(defn count (n) ((cond (< n 1) 0 :else (count (- n 1))))) (count 2) (count 3)",
+        );
+        let mut evaluator = Evaluator::new().with_max_call_depth(3);
+        let results = parser
+            .parse_all()
+            .unwrap()
+            .iter()
+            .map(|ast| evaluator.eval(ast))
+            .collect::<Vec<Result<Value, EvalError>>>();
+
+        assert_eq!(
+            results[1..],
+            [
+                Ok(Value::Number(0.0)),
+                Err(EvalError::StackOverflow { depth: 3 })
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_when_calling_non_functions() {
+        assert_eq!(
+            eval_all("(println 1) (def x 1) (x 2)"),
+            vec![
+                Err(EvalError::UndefinedVariable(String::from("println"))),
+                Ok(Value::Number(1.0)),
+                Err(EvalError::NotCallable(String::from("x"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_unsupported_expressions() {
+        assert_eq!(
+            eval_all("{:a 1}"),
+            vec![Err(EvalError::NotYetSupported(AST::MapExpr(vec![(
+                AST::KeywordExpr(String::from("a")),
+                AST::NumberExpr(1.0)
+            )])))]
+        );
+    }
+}
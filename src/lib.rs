@@ -0,0 +1,7 @@
+pub mod ast;
+pub mod eval;
+pub mod parser;
+pub mod tok;
+
+#[cfg(test)]
+mod test_support;
@@ -1,15 +1,12 @@
 #[macro_use]
 extern crate clap;
 
-pub mod ast;
-pub mod parser;
-pub mod tok;
-
 use clap::AppSettings;
-use parser::RecursiveDescentParser;
+use rust_lispy::eval::Evaluator;
+use rust_lispy::parser::RecursiveDescentParser;
+use rust_lispy::tok::{GreedyTokenizer, Token};
 use std::fs::File;
 use std::path::Path;
-use tok::{GreedyTokenizer, Token};
 
 fn main() {
     let matches = clap_app!(lispy =>
@@ -23,6 +20,12 @@ fn main() {
         (@subcommand parse =>
             (about: "Parse the file and print out the ASTs")
         )
+        (@subcommand format =>
+            (about: "Parse the file and print it back out formatted")
+        )
+        (@subcommand eval =>
+            (about: "Evaluate the file and print out the result of each expression")
+        )
     )
     .setting(AppSettings::SubcommandRequiredElseHelp)
     .get_matches();
@@ -32,9 +35,21 @@ fn main() {
         let tokenizer =
             GreedyTokenizer::new(read_file(matches.value_of("INPUT").unwrap())).unwrap();
         let mut tabs = 0;
+        let mut tokens = 0;
+        let mut lines = 0;
+        let mut errors = 0;
 
         for token in tokenizer {
-            let char_and_position = token.unwrap();
+            let char_and_position = match token {
+                Ok(char_and_position) => char_and_position,
+                Err(err) => {
+                    println!("Err: {:?}", err);
+                    errors += 1;
+                    continue;
+                }
+            };
+            tokens += 1;
+            lines = char_and_position.to.line;
 
             // if we encounter a ), reduce tabs before printing
             if char_and_position.token == Token::CloseParen {
@@ -43,7 +58,7 @@ fn main() {
 
             println!(
                 "{}{}",
-                (0..tabs).into_iter().map(|_| '\t').collect::<String>(),
+                (0..tabs).map(|_| '\t').collect::<String>(),
                 char_and_position
             );
 
@@ -52,6 +67,8 @@ fn main() {
                 tabs += 1;
             }
         }
+
+        eprintln!("{} tokens, {} lines, {} errors", tokens, lines, errors);
     }
 
     // Parser stuff
@@ -61,24 +78,94 @@ fn main() {
         let mut parser = RecursiveDescentParser::new(Box::new(tokenizer));
 
         loop {
-            match parser.next_expression() {
-                Ok(Some(something)) => println!("{:?}", something),
+            let (expression, errors) = parser.next_expression_recovering();
+            for err in errors {
+                println!("Err: {:?}", err);
+            }
+
+            match expression {
+                Some(something) => println!("{:?}", something),
+                None => break,
+            }
+        }
+    }
+
+    // Formatter stuff
+    if matches.subcommand_matches("format").is_some() {
+        let mut tokenizer = GreedyTokenizer::new(read_file(matches.value_of("INPUT").unwrap()))
+            .unwrap()
+            .with_full_fidelity(true);
+
+        // comments don't make it into the ASTs, so files with them get left alone rather than
+        // losing them
+        let comment = tokenizer
+            .by_ref()
+            .flatten()
+            .find(|token_and_span| matches!(token_and_span.token, Token::Comment(_)));
+        if let Some(comment) = comment {
+            eprintln!(
+                "Err: can't format without losing the comment on line {}",
+                comment.from.line
+            );
+            std::process::exit(1);
+        }
+
+        tokenizer.reset().unwrap();
+        let mut parser = RecursiveDescentParser::new(Box::new(tokenizer));
+
+        match parser.parse_all() {
+            Ok(expressions) => {
+                for (idx, expression) in expressions.iter().enumerate() {
+                    if idx > 0 {
+                        println!();
+                    }
+                    println!("{}", expression.pretty(FORMAT_WIDTH));
+                }
+            }
+            Err(err) => {
+                eprintln!("Err: {:?}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Evaluator stuff
+    if matches.subcommand_matches("eval").is_some() {
+        let tokenizer =
+            GreedyTokenizer::new(read_file(matches.value_of("INPUT").unwrap())).unwrap();
+        let mut parser = RecursiveDescentParser::new(Box::new(tokenizer));
+        let mut evaluator = Evaluator::new();
+
+        loop {
+            let expression = match parser.next_expression() {
+                Ok(Some(expression)) => expression,
                 Ok(None) => break,
                 Err(err) => {
-                    println!("Err: {:?}", err);
-                    break;
+                    eprintln!("Err: {:?}", err);
+                    std::process::exit(1);
+                }
+            };
+
+            match evaluator.eval(&expression) {
+                Ok(value) => println!("{}", value),
+                Err(err) => {
+                    eprintln!("Err: {:?}", err);
+                    std::process::exit(1);
                 }
             }
         }
     }
 }
 
+// how wide formatted source can get before forms get broken up over lines
+const FORMAT_WIDTH: usize = 80;
+
 fn read_file(file_path: &str) -> File {
     let path = Path::new(file_path);
     let display = path.display();
 
     // Open the path in read-only mode, returns `io::Result<File>`
-    match File::open(&path) {
+    match File::open(path) {
         Err(why) => panic!("couldn't open {}: {}", display, why),
         Ok(file) => file,
     }
@@ -1,15 +1,42 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
 use crate::ast::AST;
-use crate::tok::{Token, Position, TokenAndSpan, Tokenizer, TokenizerError};
+use crate::tok::{GreedyTokenizer, Token, Position, Span, TokenAndSpan, Tokenizer, TokenizerError};
+
+/// Transforms the form following a reader macro character into the form that gets parsed.
+pub type ReaderMacro = Box<dyn Fn(AST) -> AST>;
+
+/// How deeply forms can nest by default. Even unoptimized builds parse forms this deep on a 2MB
+/// thread stack, the smallest rust gives new threads by default.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
 
 pub struct RecursiveDescentParser {
     tokenizer: Box<dyn Tokenizer>,
+    reader_macros: HashMap<char, ReaderMacro>,
+    max_depth: usize,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
-    MismatchedParens(Position),
+    /// Covers the innermost paren, bracket or brace still open when the input ran out, up to the
+    /// last token
+    UnclosedParen(Span),
+    /// Just the close that doesn't match the innermost open, or comes when nothing is open at all
+    UnexpectedCloseParen(Span),
     FunctionNeedsABody,
     UnexpectedEof(Position),
+    OddNumberOfBindings(Position),
+    OddNumberOfMapForms(Position),
+    OddNumberOfCondForms(Position),
+    /// A `def` or `defn` somewhere other than the top level, a `do`, or a body of statements
+    DefNotAllowedHere(Position),
+    /// Where the form that went past the parser's max depth starts
+    NestingTooDeep(Position),
+    ReservedKeywordAsName {
+        keyword: String,
+        position: Position,
+    },
     UnexpectedTokenError {
         expected: Option<Token>,
         found: Option<Token>,
@@ -17,8 +44,8 @@ pub enum ParseError {
         to: Position,
     },
     UnexpectedExpressionError {
-        expected: Option<AST>,
-        found: Option<AST>,
+        expected: Option<Box<AST>>,
+        found: Option<Box<AST>>,
         position: Position,
     },
     TokenizerError(TokenizerError),
@@ -31,228 +58,925 @@ impl From<TokenizerError> for ParseError {
     }
 }
 
+/// The character a reader macro would be registered under for `token`. Most come through as
+/// `Unknown`, but the tokenizer gives `'` a token of its own.
+fn reader_macro_char(token: &Token) -> Option<char> {
+    match token {
+        Token::Quote => Some('\''),
+        Token::Unknown(chr) => Some(*chr),
+        _ => None,
+    }
+}
+
+fn has_reader_macro(reader_macros: &HashMap<char, ReaderMacro>, token: &Token) -> bool {
+    reader_macro_char(token).is_some_and(|chr| reader_macros.contains_key(&chr))
+}
+
 impl RecursiveDescentParser {
     pub fn new(tokenizer: Box<dyn Tokenizer>) -> Self {
-        Self { tokenizer }
+        let mut parser = Self {
+            tokenizer,
+            reader_macros: HashMap::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        };
+
+        // quoting keeps the form that follows as it is
+        parser.register_reader_macro('\'', |form| AST::QuoteExpr(Box::new(form)));
+        parser
+    }
+
+    /// Caps how deeply forms can nest inside one another, counting the forms that quotes and
+    /// reader macros apply to as well. Parsing recurses once per level, so anything deeper fails
+    /// with `NestingTooDeep` instead. Defaults to `DEFAULT_MAX_DEPTH`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Builds a parser over a source string, with a default `GreedyTokenizer` reading from it.
+    ///
+    /// ```
+    /// use rust_lispy::ast::AST;
+    /// use rust_lispy::parser::RecursiveDescentParser;
+    ///
+    /// let expression = RecursiveDescentParser::from_source("(+ 1 2)").next_expression();
+    /// assert_eq!(
+    ///     *expression.unwrap().unwrap(),
+    ///     AST::EvaluateExpr {
+    ///         callee: String::from("+"),
+    ///         args: vec![AST::NumberExpr(1.0), AST::NumberExpr(2.0)],
+    ///     }
+    /// );
+    /// ```
+    pub fn from_source(source: &str) -> Self {
+        let tokenizer = GreedyTokenizer::new(Cursor::new(source.as_bytes().to_vec()))
+            .expect("a str is valid UTF-8 and reading from memory can't fail");
+        Self::new(Box::new(tokenizer))
+    }
+
+    /// Registers a reader macro: whenever `chr` shows up in front of a form, the form is parsed
+    /// and handed to `handler`, and whatever it returns takes the place of both. `'` comes
+    /// registered to quote the form, but can be replaced like any other.
+    pub fn register_reader_macro<F>(&mut self, chr: char, handler: F)
+    where
+        F: Fn(AST) -> AST + 'static,
+    {
+        self.reader_macros.insert(chr, Box::new(handler));
     }
 
     pub fn next_expression(&mut self) -> Result<Option<Box<AST>>, ParseError> {
-        let tokens_and_spans = Self::extract_until_brackets_match(&mut self.tokenizer)?;
+        self.next_expression_with_consumed()
+            .map(|(expression, _)| expression)
+    }
+
+    /// Parses every remaining top-level expression, stopping at the first error.
+    pub fn parse_all(&mut self) -> Result<Vec<AST>, ParseError> {
+        let mut expressions = vec![];
+        while let Some(expression) = self.next_expression()? {
+            expressions.push(*expression);
+        }
+        Ok(expressions)
+    }
+
+    /// Parses the next top-level expression, skipping past any malformed ones before it instead
+    /// of stopping at the first. Returns the expression, or `None` at the end of the input, along
+    /// with the errors for every form that got skipped.
+    pub fn next_expression_recovering(&mut self) -> (Option<AST>, Vec<ParseError>) {
+        let mut errors = vec![];
+        loop {
+            match self.next_expression() {
+                Ok(expression) => return (expression.map(|expression| *expression), errors),
+                // there's no reading past a failing source
+                Err(err @ ParseError::TokenizerError(TokenizerError::IoError(_))) => {
+                    errors.push(err);
+                    return (None, errors);
+                }
+                // every form gets pulled off the tokenizer whole, so the next one starts clean
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+
+    /// Same as `next_expression`, but also reports how many tokens were pulled off the tokenizer
+    /// to build the expression, so a host driving the tokenizer can resume right after it.
+    pub fn next_expression_with_consumed(
+        &mut self,
+    ) -> Result<(Option<Box<AST>>, usize), ParseError> {
+        // the parser skips whitespace and comments, but they still got pulled off the tokenizer
+        let mut pulled = 0;
+        let tokens_and_spans = Self::extract_until_brackets_match(
+            &mut self.tokenizer.by_ref().inspect(|_| pulled += 1),
+            &self.reader_macros,
+            self.max_depth,
+        )?;
 
         if tokens_and_spans.is_empty() {
-            Ok(None)
+            Ok((None, pulled))
         } else {
-            let (mut asts, _) = Self::recursively_evaluate(&tokens_and_spans[..])?;
+            let (mut asts, _) = self.recursively_evaluate_statements(&tokens_and_spans[..])?;
             match asts.len() {
-                1 => Ok(Some(Box::new(asts.pop().unwrap()))),
+                1 => Ok((Some(Box::new(asts.pop().unwrap())), pulled)),
                 num_terms if num_terms > 1 => Err(ParseError::UnknownError(String::from("Not sure how we got here, but we have multiple statements with the same open/close brackets"))),
                 _ => Err(ParseError::UnknownError(String::from("Here we are but how")))
             }
         }
     }
 
+    /// Parses forms that are values, like a call's args, where a def isn't allowed.
     fn recursively_evaluate(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+    ) -> Result<(Vec<AST>, usize), ParseError> {
+        self.evaluate_forms(tokens_and_spans, false)
+    }
+
+    /// Parses forms that are statements, like the top level or a `do`, where a def is allowed.
+    fn recursively_evaluate_statements(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+    ) -> Result<(Vec<AST>, usize), ParseError> {
+        self.evaluate_forms(tokens_and_spans, true)
+    }
+
+    // every level of nesting recurses through here, so each form gets parsed in a function of its
+    // own and this one's stack frame stays small
+    fn evaluate_forms(
+        &self,
         tokens_and_spans: &[TokenAndSpan],
+        statements: bool,
     ) -> Result<(Vec<AST>, usize), ParseError> {
         let mut result = Vec::with_capacity(tokens_and_spans.len());
         let mut parsed = 0;
-        loop {
-            if parsed < tokens_and_spans.len() {
-                match tokens_and_spans[parsed].token {
-                    Token::Number(val) => result.push(AST::NumberExpr(val)),
-                    Token::Identifier(ref name) => {
-                        result.push(AST::VariableExpr(String::from(name)))
-                    }
+        while parsed < tokens_and_spans.len() {
+            let form_and_parsed = match tokens_and_spans[parsed].token {
+                Token::Number(val) => Ok((AST::NumberExpr(val), 0)),
+                Token::Bool(val) => Ok((AST::BoolExpr(val), 0)),
+                Token::Nil => Ok((AST::NilExpr, 0)),
+                Token::String(ref text) => Ok((AST::StringExpr(text.clone()), 0)),
+                Token::Keyword(ref name) => Ok((AST::KeywordExpr(name.clone()), 0)),
+                Token::Identifier(ref name) => Ok((AST::VariableExpr(String::from(name)), 0)),
+
+                // a def only makes sense as a statement, so it can't go where a value should
+                Token::Def | Token::Defn if !statements => Err(ParseError::DefNotAllowedHere(
+                    tokens_and_spans[parsed].from.clone(),
+                )),
+                Token::Def => self.parse_def(tokens_and_spans, parsed),
+                Token::Defn => self.parse_defn(tokens_and_spans, parsed),
+                Token::Fn => self.parse_function(tokens_and_spans, parsed),
+                Token::Do => self.parse_do(tokens_and_spans, parsed),
+                Token::Cond => self.parse_cond(tokens_and_spans, parsed),
+                Token::If => self.parse_if(tokens_and_spans, parsed),
+                Token::Let => self.parse_let(tokens_and_spans, parsed),
+                Token::Ns => {
+                    Self::name_after(tokens_and_spans, parsed).map(|name| (AST::NsExpr(name), 1))
+                }
+                Token::ThreadFirst | Token::ThreadLast => {
+                    self.parse_threading(tokens_and_spans, parsed)
+                }
+                Token::SomeThreadFirst | Token::SomeThreadLast => {
+                    self.parse_some_threading(tokens_and_spans, parsed)
+                }
 
-                    Token::Def => {
-                        if let Token::Identifier(name) = &tokens_and_spans[parsed + 1].token {
-                            let (mut rhs, rec_parsed) =
-                                Self::recursively_evaluate(&tokens_and_spans[parsed + 2..])?;
-
-                            if rhs.len() > 1 {
-                                return Err(ParseError::UnexpectedExpressionError {
-                                    expected: None,
-                                    found: rhs.get(1).cloned(),
-                                    position: tokens_and_spans[parsed + 3].from.clone()
-                                });
-                            }
-
-                            result.push(AST::EvaluateExpr {
-                                callee: String::from("__assign"),
-                                args: vec![AST::VariableExpr(name.clone()), rhs.pop().unwrap()],
-                            });
-
-                            // we also parsed the next two tokens
-                            parsed += 1 + rec_parsed;
-                        } else {
-                            return Err(ParseError::UnexpectedTokenError {
-                                expected: Some(Token::Identifier(String::from("_"))),
-                                found: Some(tokens_and_spans[parsed + 1].token.clone()),
-                                from: tokens_and_spans[parsed + 1].from.clone(),
-                                to: tokens_and_spans[parsed + 1].to.clone(),
-                            });
-                        }
-                    }
+                // open paren tokens indicate we should go down one level in parsing things
+                Token::OpenParen => self.parse_paren_form(tokens_and_spans, parsed, statements),
+                Token::OpenBracket => self.parse_vector(tokens_and_spans, parsed),
+                Token::OpenBrace => self.parse_map(tokens_and_spans, parsed),
+
+                // close paren tokens indicate we should go up one level, and so return
+                Token::CloseParen | Token::CloseBracket | Token::CloseBrace => break,
+
+                Token::Whitespace(_) | Token::Comment(_) => {
+                    parsed += 1;
+                    continue;
+                }
 
-                    Token::Fn => {
-                        if let Token::OpenParen = &tokens_and_spans[parsed + 1].token {
-                            let mut total_tokens_parsed = 0;
-
-                            // parse the args, make sure we have an open brancket and then get ourselves the tokens within them
-                            let args_and_spans =
-                                Self::find_tokens_within_brackets(&tokens_and_spans[parsed + 1..])?;
-                            let mut parameters = vec![];
-                            for arg_and_span in args_and_spans {
-                                if let Token::Identifier(ref arg_name) = arg_and_span.token {
-                                    parameters.push(String::from(arg_name))
-                                } else {
-                                    return Err(ParseError::UnexpectedTokenError {
-                                        expected: Some(Token::Identifier(String::from("_"))),
-                                        found: Some(arg_and_span.token.clone()),
-                                        from: arg_and_span.from.clone(),
-                                        to: arg_and_span.to.clone()
-                                    });
-                                }
-                            }
-
-                            total_tokens_parsed += 2 + parameters.len();  // include the bracket open and close
-
-                            // parse the body of the function
-                            if tokens_and_spans[parsed + total_tokens_parsed + 1].token
-                                != Token::OpenParen
-                            {
-                                return Err(ParseError::UnexpectedTokenError {
-                                    expected: Some(Token::OpenParen),
-                                    found: Some(
-                                        tokens_and_spans[parsed + total_tokens_parsed + 1]
-                                            .token
-                                            .clone(),
-                                    ),
-                                    from: tokens_and_spans[parsed + total_tokens_parsed + 1]
-                                        .from
-                                        .clone(),
-                                    to: tokens_and_spans[parsed + total_tokens_parsed + 1]
-                                        .to
-                                        .clone(),
-                                });
-                            }
-
-                            let function_body_tokens = Self::find_tokens_within_brackets(
-                                &tokens_and_spans[parsed + total_tokens_parsed + 1..],
-                            )?;
-                            let (statements, rec_parsed) =
-                                Self::recursively_evaluate(function_body_tokens)?;
-
-                            if rec_parsed == 0 {
-                                return Err(ParseError::FunctionNeedsABody);
-                            }
-
-                            total_tokens_parsed += 2 + rec_parsed;  // include the bracket open and close
-
-                            result.push(AST::FunctionExpr {
-                                parameters,
-                                statements,
-                            });
-
-                            parsed += total_tokens_parsed;
-                        } else {
-                            return Err(ParseError::UnexpectedTokenError {
-                                expected: Some(Token::OpenParen),
-                                found: Some(tokens_and_spans[parsed + 1].token.clone()),
-                                from: tokens_and_spans[parsed + 1].from.clone(),
-                                to: tokens_and_spans[parsed + 1].to.clone(),
-                            });
-                        }
+                ref token @ (Token::Quote | Token::Unknown(_)) => {
+                    match reader_macro_char(token).and_then(|chr| self.reader_macros.get(&chr)) {
+                        // reader macros transform the form that follows them
+                        Some(reader_macro) => self
+                            .parse_next_form(tokens_and_spans, parsed)
+                            .map(|(form, form_parsed)| (reader_macro(form), form_parsed)),
+                        None => Err(ParseError::UnexpectedTokenError {
+                            expected: None,
+                            found: Some(token.clone()),
+                            from: tokens_and_spans[parsed].from.clone(),
+                            to: tokens_and_spans[parsed].to.clone(),
+                        }),
                     }
+                }
+
+                // only mean something in a function's parameters, or at the head of a paren form
+                Token::Ampersand | Token::QuoteForm => Err(ParseError::UnexpectedTokenError {
+                    expected: None,
+                    found: Some(tokens_and_spans[parsed].token.clone()),
+                    from: tokens_and_spans[parsed].from.clone(),
+                    to: tokens_and_spans[parsed].to.clone(),
+                }),
+            };
+
+            let (form, form_parsed) = form_and_parsed?;
+            result.push(form);
+            parsed += 1 + form_parsed;
+        }
+
+        Ok((result, parsed))
+    }
+
+    // Like `parse_function`, each of these parses the form whose first token is at `parsed`, and
+    // returns it along with how many tokens past `parsed` it took up.
+
+    fn parse_def(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let name = Self::name_after(tokens_and_spans, parsed)?;
+        let (mut rhs, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 2..])?;
+
+        // the form can't end before we get a value either
+        if rhs.is_empty() {
+            return Err(ParseError::UnexpectedEof(
+                tokens_and_spans[parsed + 1].to.clone(),
+            ));
+        }
+
+        if rhs.len() > 1 {
+            return Err(ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: rhs.get(1).cloned().map(Box::new),
+                position: tokens_and_spans[parsed + 3].from.clone()
+            });
+        }
+
+        let assignment = AST::EvaluateExpr {
+            callee: String::from("__assign"),
+            args: vec![AST::VariableExpr(name), rhs.pop().unwrap()],
+        };
+
+        // we also parsed the next two tokens
+        Ok((assignment, 1 + rec_parsed))
+    }
+
+    /// defn is sugar for a def whose value is a fn.
+    fn parse_defn(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let name = Self::name_after(tokens_and_spans, parsed)?;
+        let (function, total_tokens_parsed) = self.parse_function(tokens_and_spans, parsed + 1)?;
+
+        let assignment = AST::EvaluateExpr {
+            callee: String::from("__assign"),
+            args: vec![AST::VariableExpr(name), function],
+        };
+        Ok((assignment, 1 + total_tokens_parsed))
+    }
+
+    /// do runs everything after it in order.
+    fn parse_do(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (body, rec_parsed) =
+            self.recursively_evaluate_statements(&tokens_and_spans[parsed + 1..])?;
+        Ok((AST::DoExpr(body), rec_parsed))
+    }
+
+    /// cond takes tests, each followed by the result to give back if it passes.
+    fn parse_cond(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (forms, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+
+        if forms.len() % 2 != 0 {
+            // report it at the close paren, when the cond has one
+            let end = tokens_and_spans
+                .get(parsed + rec_parsed + 1)
+                .unwrap_or(&tokens_and_spans[parsed + rec_parsed]);
+            return Err(ParseError::OddNumberOfCondForms(end.from.clone()));
+        }
+
+        let clauses = forms
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok((AST::CondExpr(clauses), rec_parsed))
+    }
+
+    fn parse_if(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (mut branches, rec_parsed) =
+            self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+
+        // we need a condition, a then branch and optionally an else branch
+        if branches.len() < 2 || branches.len() > 3 {
+            return Err(ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: branches.get(3).cloned().map(Box::new),
+                position: tokens_and_spans[parsed].from.clone(),
+            });
+        }
+
+        let else_branch = if branches.len() == 3 {
+            branches.pop().map(Box::new)
+        } else {
+            None
+        };
+        let then_branch = Box::new(branches.pop().unwrap());
+        let condition = Box::new(branches.pop().unwrap());
+
+        Ok((
+            AST::IfExpr {
+                condition,
+                then_branch,
+                else_branch,
+            },
+            rec_parsed,
+        ))
+    }
+
+    /// Threading macros get expanded into the nested calls they stand for.
+    fn parse_threading(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (forms, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+        let position = tokens_and_spans[parsed].from.clone();
 
-                    // open paren tokens indicate we should go down one level in parsing things
-                    Token::OpenParen => {
-                        let (stuff, rec_parsed) =
-                            Self::recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
-                        parsed += rec_parsed;
-
-                        // if we have a variable and then some shit, let's return it as an EvaluateExpr
-                        match stuff[..].split_first() {
-                            Some((AST::VariableExpr(ref name), rest)) => {
-                                result.push(AST::EvaluateExpr {
-                                    callee: String::from(name),
-                                    args: rest.to_vec(),
-                                })
-                            }
-                            Some((AST::EvaluateExpr { callee, args }, [])) => {
-                                result.push(AST::EvaluateExpr {
-                                    callee: callee.clone(),
-                                    args: args.clone(),
-                                })
-                            }
-                            Some((AST::FunctionExpr {parameters, statements}, [])) => {
-                                result.push(AST::FunctionExpr {
-                                    parameters: parameters.clone(),
-                                    statements: statements.clone()
-                                })
-                            }
-                            _ => {
-                                return Err(ParseError::UnexpectedExpressionError {
-                                    expected: Some(AST::VariableExpr(String::from("_"))),
-                                    found: stuff.first().cloned(),
-                                    position: tokens_and_spans[parsed].from.clone(),
-                                })
-                            }
-                        }
+        if forms.len() < 2 {
+            return Err(ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: forms.first().cloned().map(Box::new),
+                position,
+            });
+        }
+
+        let thread_last = tokens_and_spans[parsed].token == Token::ThreadLast;
+        let mut forms = forms.into_iter();
+        let mut threaded = forms.next().unwrap();
+        for form in forms {
+            threaded = match form {
+                AST::EvaluateExpr { callee, mut args } => {
+                    if thread_last {
+                        args.push(threaded);
+                    } else {
+                        args.insert(0, threaded);
+                    }
+                    AST::EvaluateExpr { callee, args }
+                }
+                // calls to other forms take the threaded value as an arg the same way
+                AST::ApplyExpr { function, mut args } => {
+                    if thread_last {
+                        args.push(threaded);
+                    } else {
+                        args.insert(0, threaded);
                     }
+                    AST::ApplyExpr { function, args }
+                }
+                AST::VariableExpr(callee) => AST::EvaluateExpr {
+                    callee,
+                    args: vec![threaded],
+                },
+                other => {
+                    return Err(ParseError::UnexpectedExpressionError {
+                        expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                        found: Some(Box::new(other)),
+                        position,
+                    })
+                }
+            };
+        }
+
+        Ok((threaded, rec_parsed))
+    }
 
-                    // close paren tokens indicate we should go up one level, and so return
-                    Token::CloseParen => break,
+    /// The nil-safe threading macros have to check each step as it runs, so they're kept as is.
+    fn parse_some_threading(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (forms, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+        let position = tokens_and_spans[parsed].from.clone();
+
+        if forms.len() < 2 {
+            return Err(ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: forms.first().cloned().map(Box::new),
+                position,
+            });
+        }
 
-                    Token::Unknown(chr) => return Err(ParseError::UnexpectedTokenError {
-                        expected: None,
-                        found: Some(Token::Unknown(chr)),
-                        from: tokens_and_spans[parsed].from.clone(),
-                        to: tokens_and_spans[parsed].to.clone(),
+        let mut forms = forms.into_iter();
+        let initial = Box::new(forms.next().unwrap());
+        let mut steps = vec![];
+        for form in forms {
+            match form {
+                AST::EvaluateExpr { .. } | AST::VariableExpr(_) | AST::ApplyExpr { .. } => {
+                    steps.push(form)
+                }
+                other => {
+                    return Err(ParseError::UnexpectedExpressionError {
+                        expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                        found: Some(Box::new(other)),
+                        position,
                     })
+                }
+            }
+        }
+
+        Ok((
+            AST::SomeThreadExpr {
+                initial,
+                steps,
+                thread_last: tokens_and_spans[parsed].token == Token::SomeThreadLast,
+            },
+            rec_parsed,
+        ))
+    }
+
+    /// The one whole form after a quote or reader macro.
+    fn parse_next_form(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let form_tokens = self.slice_next_form(
+            &tokens_and_spans[parsed + 1..],
+            &tokens_and_spans[parsed].to,
+        )?;
+        let (mut form, _) = self.recursively_evaluate(form_tokens)?;
+
+        Ok((form.pop().unwrap(), form_tokens.len()))
+    }
+
+    fn parse_let(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        // the bindings come first, in a vector
+        match tokens_and_spans.get(parsed + 1) {
+            Some(TokenAndSpan { token: Token::OpenBracket, .. }) => {}
+            None | Some(TokenAndSpan { token: Token::CloseParen, .. }) => {
+                return Err(ParseError::UnexpectedEof(
+                    tokens_and_spans[parsed].to.clone(),
+                ))
+            }
+            Some(TokenAndSpan { token, from, to }) => {
+                return Err(ParseError::UnexpectedTokenError {
+                    expected: Some(Token::OpenBracket),
+                    found: Some(token.clone()),
+                    from: from.clone(),
+                    to: to.clone(),
+                })
+            }
+        }
+
+        let (binding_forms, bindings_parsed) =
+            self.recursively_evaluate(&tokens_and_spans[parsed + 2..])?;
+        let close_idx = parsed + 2 + bindings_parsed;
+        Self::check_closed_by(tokens_and_spans, close_idx, Token::CloseBracket)?;
 
+        if binding_forms.len() % 2 != 0 {
+            return Err(ParseError::OddNumberOfBindings(
+                tokens_and_spans[close_idx].from.clone(),
+            ));
+        }
+
+        let mut bindings = vec![];
+        for pair in binding_forms.chunks(2) {
+            match &pair[0] {
+                AST::VariableExpr(name) => {
+                    bindings.push((name.clone(), pair[1].clone()))
                 }
-            } else {
-                break;
+                other => {
+                    return Err(ParseError::UnexpectedExpressionError {
+                        expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                        found: Some(Box::new(other.clone())),
+                        position: tokens_and_spans[parsed + 1].from.clone(),
+                    })
+                }
+            }
+        }
+
+        // then every form up to the close paren is the body
+        let (body, body_parsed) =
+            self.recursively_evaluate_statements(&tokens_and_spans[close_idx + 1..])?;
+
+        Ok((AST::LetExpr { bindings, body }, 2 + bindings_parsed + body_parsed))
+    }
+
+    /// Whatever's in a pair of parens, which is mostly calls, but is also how special forms like
+    /// `if` or `fn` get written.
+    fn parse_paren_form(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+        statements: bool,
+    ) -> Result<(AST, usize), ParseError> {
+        // quote is only a special form right at the head
+        if let Some(TokenAndSpan { token: Token::QuoteForm, .. }) = tokens_and_spans.get(parsed + 1)
+        {
+            return self.parse_quote_form(tokens_and_spans, parsed);
+        }
+
+        // the one place a def goes is the head of a paren form that's a statement; anywhere else,
+        // the args get parsed as values and turn it down
+        if statements {
+            let head = tokens_and_spans.get(parsed + 1).map(|head| &head.token);
+            if let Some(Token::Def | Token::Defn) = head {
+                return self.parse_def_form(tokens_and_spans, parsed);
+            }
+        }
+
+        let head_is_form = matches!(
+            tokens_and_spans.get(parsed + 1),
+            Some(TokenAndSpan { token: Token::OpenParen, .. })
+        );
+        let (stuff, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+        let close_idx = parsed + 1 + rec_parsed;
+        Self::check_closed_by(tokens_and_spans, close_idx, Token::CloseParen)?;
+
+        // if we have a variable and then some shit, let's return it as an EvaluateExpr
+        let form = match stuff[..].split_first() {
+            // calling the result of another form, like a fn written inline, even with no args
+            Some((function, args)) if head_is_form => AST::ApplyExpr {
+                function: Box::new(function.clone()),
+                args: args.to_vec(),
+            },
+            Some((AST::VariableExpr(ref name), rest)) => {
+                AST::EvaluateExpr {
+                    callee: String::from(name),
+                    args: rest.to_vec(),
+                }
+            }
+            Some((AST::EvaluateExpr { callee, args }, [])) => {
+                AST::EvaluateExpr {
+                    callee: callee.clone(),
+                    args: args.clone(),
+                }
+            }
+            Some((function @ AST::FunctionExpr { .. }, [])) => function.clone(),
+            Some((
+                expr @ (AST::IfExpr { .. }
+                | AST::LetExpr { .. }
+                | AST::DoExpr(_)
+                | AST::CondExpr(_)
+                | AST::NsExpr(_)
+                | AST::SomeThreadExpr { .. }
+                | AST::ApplyExpr { .. }),
+                [],
+            )) => expr.clone(),
+            _ => {
+                return Err(ParseError::UnexpectedExpressionError {
+                    expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                    found: stuff.first().cloned().map(Box::new),
+                    position: tokens_and_spans[close_idx].from.clone(),
+                })
             }
+        };
+
+        Ok((form, 1 + rec_parsed))  // include the close paren
+    }
+
+    /// `(def x 1)` or `(defn f (a) a)`, with nothing after the def's value.
+    fn parse_def_form(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (assignment, def_parsed) = match tokens_and_spans[parsed + 1].token {
+            Token::Def => self.parse_def(tokens_and_spans, parsed + 1)?,
+            _ => self.parse_defn(tokens_and_spans, parsed + 1)?,
+        };
+        Self::check_closed_by(tokens_and_spans, parsed + 2 + def_parsed, Token::CloseParen)?;
+
+        Ok((assignment, 2 + def_parsed))  // include the close paren
+    }
 
-            parsed += 1;
+    /// `(quote x)`, which keeps the one form inside it as it is, just like `'x`.
+    fn parse_quote_form(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (form, form_parsed) = self.parse_next_form(tokens_and_spans, parsed + 1)?;
+        let close_idx = parsed + 2 + form_parsed;
+
+        match tokens_and_spans.get(close_idx) {
+            None => {
+                return Err(ParseError::UnexpectedEof(
+                    tokens_and_spans[close_idx - 1].to.clone(),
+                ))
+            }
+            Some(TokenAndSpan {
+                token: Token::CloseParen | Token::CloseBracket | Token::CloseBrace,
+                ..
+            }) => Self::check_closed_by(tokens_and_spans, close_idx, Token::CloseParen)?,
+            // there's only ever the one form to quote
+            Some(token_and_span) => {
+                return Err(ParseError::UnexpectedTokenError {
+                    expected: Some(Token::CloseParen),
+                    found: Some(token_and_span.token.clone()),
+                    from: token_and_span.from.clone(),
+                    to: token_and_span.to.clone(),
+                })
+            }
         }
 
-        Ok((result, parsed))
+        Ok((AST::QuoteExpr(Box::new(form)), 2 + form_parsed))  // include the close paren
+    }
+
+    /// Open bracket tokens start a vector of whatever's inside them.
+    fn parse_vector(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (items, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+        let close_idx = parsed + 1 + rec_parsed;
+        Self::check_closed_by(tokens_and_spans, close_idx, Token::CloseBracket)?;
+
+        Ok((AST::ListExpr(items), 1 + rec_parsed))  // include the close bracket
+    }
+
+    /// Open brace tokens start a map, of keys each followed by their value.
+    fn parse_map(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        let (forms, rec_parsed) = self.recursively_evaluate(&tokens_and_spans[parsed + 1..])?;
+        let close_idx = parsed + 1 + rec_parsed;
+        Self::check_closed_by(tokens_and_spans, close_idx, Token::CloseBrace)?;
+
+        if forms.len() % 2 != 0 {
+            return Err(ParseError::OddNumberOfMapForms(
+                tokens_and_spans[close_idx].from.clone(),
+            ));
+        }
+
+        let entries = forms
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok((AST::MapExpr(entries), 1 + rec_parsed))  // include the close brace
+    }
+
+    /// The name that comes right after the token at `parsed`, as in `(def name ...)`.
+    fn name_after(tokens_and_spans: &[TokenAndSpan], parsed: usize) -> Result<String, ParseError> {
+        // the form can't end before we get a name
+        let name_and_span = match tokens_and_spans.get(parsed + 1) {
+            None | Some(TokenAndSpan { token: Token::CloseParen, .. }) => {
+                return Err(ParseError::UnexpectedEof(tokens_and_spans[parsed].to.clone()))
+            }
+            Some(name_and_span) => name_and_span,
+        };
+        Self::check_not_reserved(name_and_span)?;
+
+        match &name_and_span.token {
+            Token::Identifier(name) => Ok(name.clone()),
+            token => Err(ParseError::UnexpectedTokenError {
+                expected: Some(Token::Identifier(String::from("_"))),
+                found: Some(token.clone()),
+                from: name_and_span.from.clone(),
+                to: name_and_span.to.clone(),
+            }),
+        }
+    }
+
+    /// The name a function parameter binds, which has to be a plain identifier.
+    fn parameter_name(arg_and_span: &TokenAndSpan) -> Result<String, ParseError> {
+        Self::check_not_reserved(arg_and_span)?;
+        if let Token::Identifier(ref arg_name) = arg_and_span.token {
+            Ok(String::from(arg_name))
+        } else {
+            Err(ParseError::UnexpectedTokenError {
+                expected: Some(Token::Identifier(String::from("_"))),
+                found: Some(arg_and_span.token.clone()),
+                from: arg_and_span.from.clone(),
+                to: arg_and_span.to.clone()
+            })
+        }
+    }
+
+    /// Parses a function whose parameter list comes right after the token at `parsed`, returning
+    /// it along with how many tokens past `parsed` it took up.
+    fn parse_function(
+        &self,
+        tokens_and_spans: &[TokenAndSpan],
+        parsed: usize,
+    ) -> Result<(AST, usize), ParseError> {
+        // the tokens could run out anywhere past the fn, which means it got cut off
+        let last_position = tokens_and_spans[tokens_and_spans.len() - 1].to.clone();
+        if parsed + 1 == tokens_and_spans.len() {
+            return Err(ParseError::UnexpectedEof(last_position));
+        }
+
+        if let Token::OpenParen = &tokens_and_spans[parsed + 1].token {
+            let mut total_tokens_parsed = 0;
+
+            // parse the args, make sure we have an open brancket and then get ourselves the tokens within them
+            let args_and_spans = Self::find_tokens_within_brackets(&tokens_and_spans[parsed + 1..])
+                .map_err(|_| ParseError::UnexpectedEof(last_position.clone()))?;
+            // anything after an & gets collected into the one rest parameter
+            let (fixed_args, rest_args) = match args_and_spans
+                .iter()
+                .position(|arg_and_span| arg_and_span.token == Token::Ampersand)
+            {
+                Some(ampersand_idx) => (&args_and_spans[..ampersand_idx], Some(&args_and_spans[ampersand_idx..])),
+                None => (args_and_spans, None),
+            };
+
+            let mut parameters = vec![];
+            for arg_and_span in fixed_args {
+                parameters.push(Self::parameter_name(arg_and_span)?);
+            }
+
+            let rest_parameter = match rest_args {
+                Some([_, rest_and_span]) => Some(Self::parameter_name(rest_and_span)?),
+                Some([ampersand]) => {
+                    return Err(ParseError::UnexpectedTokenError {
+                        expected: Some(Token::Identifier(String::from("_"))),
+                        found: None,
+                        from: ampersand.from.clone(),
+                        to: ampersand.to.clone(),
+                    })
+                }
+                Some([_, _, extra_and_span, ..]) => {
+                    return Err(ParseError::UnexpectedTokenError {
+                        expected: Some(Token::CloseParen),
+                        found: Some(extra_and_span.token.clone()),
+                        from: extra_and_span.from.clone(),
+                        to: extra_and_span.to.clone(),
+                    })
+                }
+                _ => None,
+            };
+
+            total_tokens_parsed += 2 + args_and_spans.len();  // include the bracket open and close
+
+            // parse the body of the function
+            let body_idx = parsed + total_tokens_parsed + 1;
+            match tokens_and_spans.get(body_idx) {
+                None | Some(TokenAndSpan { token: Token::CloseParen, .. }) => {
+                    return Err(ParseError::FunctionNeedsABody)
+                }
+                Some(TokenAndSpan { token: Token::OpenParen, .. }) => {}
+                Some(TokenAndSpan { token, from, to }) => {
+                    return Err(ParseError::UnexpectedTokenError {
+                        expected: Some(Token::OpenParen),
+                        found: Some(token.clone()),
+                        from: from.clone(),
+                        to: to.clone(),
+                    })
+                }
+            }
+
+            let function_body_tokens = Self::find_tokens_within_brackets(&tokens_and_spans[body_idx..])
+                .map_err(|_| ParseError::UnexpectedEof(last_position.clone()))?;
+            let (statements, rec_parsed) =
+                self.recursively_evaluate_statements(function_body_tokens)?;
+
+            if rec_parsed == 0 {
+                return Err(ParseError::FunctionNeedsABody);
+            }
+
+            total_tokens_parsed += 2 + rec_parsed;  // include the bracket open and close
+
+            Ok((
+                AST::FunctionExpr {
+                    parameters,
+                    rest_parameter,
+                    statements,
+                },
+                total_tokens_parsed,
+            ))
+        } else {
+            Err(ParseError::UnexpectedTokenError {
+                expected: Some(Token::OpenParen),
+                found: Some(tokens_and_spans[parsed + 1].token.clone()),
+                from: tokens_and_spans[parsed + 1].from.clone(),
+                to: tokens_and_spans[parsed + 1].to.clone(),
+            })
+        }
+    }
+
+    /// Makes sure a form is closed by the right kind of token, so `(1 2]` doesn't slip through.
+    fn check_closed_by(
+        tokens_and_spans: &[TokenAndSpan],
+        close_idx: usize,
+        close: Token,
+    ) -> Result<(), ParseError> {
+        match tokens_and_spans.get(close_idx) {
+            Some(token_and_span) if token_and_span.token != close => {
+                Err(ParseError::UnexpectedCloseParen(token_and_span.span()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_not_reserved(name_and_span: &TokenAndSpan) -> Result<(), ParseError> {
+        match name_and_span.token.keyword() {
+            Some(keyword) => Err(ParseError::ReservedKeywordAsName {
+                keyword: String::from(keyword),
+                position: name_and_span.from.clone(),
+            }),
+            None => Ok(()),
+        }
     }
 
     fn extract_until_brackets_match<T>(
         tokens_and_spans: &mut T,
+        reader_macros: &HashMap<char, ReaderMacro>,
+        max_depth: usize,
     ) -> Result<Vec<TokenAndSpan>, ParseError>
     where
         T: Iterator<Item = Result<TokenAndSpan, TokenizerError>>,
     {
         let mut paren_count = 0;
+        let mut open_parens = vec![];
         let mut extracted_tokens: Vec<TokenAndSpan> = vec![];
+        let mut bad_token = None;
+
+        // every open form and every quote or reader macro waiting on a form is a level of
+        // nesting, so we keep track of how many prefixes went in front of each open form
+        let mut depth = 0;
+        let mut prefixes = 0;
+        let mut prefixes_of_open_forms = vec![];
 
         for maybe_token_and_span in tokens_and_spans {
-            let token_and_span = maybe_token_and_span?;
+            let token_and_span = match maybe_token_and_span {
+                Ok(token_and_span) => token_and_span,
+                Err(err @ TokenizerError::IoError(_)) => return Err(ParseError::from(err)),
+                Err(err) if paren_count <= 0 => return Err(ParseError::from(err)),
+                // a bad token in the middle of a form still has the rest of the form to skip
+                Err(err) => {
+                    bad_token.get_or_insert(ParseError::from(err));
+                    continue;
+                }
+            };
+            let mut awaiting_form = false;
             match token_and_span.token {
-                Token::OpenParen => paren_count += 1,
-                Token::CloseParen => paren_count -= 1,
-                _ => {}
+                // whitespace and comments carry no meaning for the parser
+                Token::Whitespace(_) | Token::Comment(_) => continue,
+                Token::OpenParen | Token::OpenBracket | Token::OpenBrace => {
+                    paren_count += 1;
+                    open_parens.push(token_and_span.span());
+                    depth += 1;
+                    prefixes_of_open_forms.push(prefixes);
+                    prefixes = 0;
+                }
+                Token::CloseParen | Token::CloseBracket | Token::CloseBrace => {
+                    paren_count -= 1;
+                    open_parens.pop();
+                    // the form is done, along with the prefixes that were waiting on it
+                    depth -= prefixes + prefixes_of_open_forms.pop().map_or(0, |open| open + 1);
+                    prefixes = 0;
+                }
+                ref token if has_reader_macro(reader_macros, token) => awaiting_form = true,
+                // an atom is a whole form, so any prefixes waiting on it are done
+                _ => {
+                    depth -= prefixes;
+                    prefixes = 0;
+                }
+            }
+            if awaiting_form {
+                depth += 1;
+                prefixes += 1;
+            }
+
+            // anything past the max depth still gets skipped, so the next form starts clean
+            if depth > max_depth {
+                bad_token.get_or_insert(ParseError::NestingTooDeep(token_and_span.from.clone()));
             }
 
             // add token to the result
             extracted_tokens.push(token_and_span);
 
-            // if we don't have open or closed parens remaining, let's return
-            if paren_count <= 0 {
+            // if we don't have open or closed parens remaining, let's return - unless a reader
+            // macro still needs the form that follows it
+            if paren_count <= 0 && !awaiting_form {
                 break;
             }
         }
 
         // if we matched all parens, we're good
-        if paren_count != 0 {
-            Err(ParseError::MismatchedParens(
-                extracted_tokens.last().unwrap().from.clone()
-            ))
+        if let Some(err) = bad_token {
+            Err(err)
+        } else if paren_count < 0 {
+            Err(ParseError::UnexpectedCloseParen(extracted_tokens.last().unwrap().span()))
+        } else if let Some(open) = open_parens.pop() {
+            Err(ParseError::UnclosedParen(open.merge(&extracted_tokens.last().unwrap().span())))
         } else {
             Ok(extracted_tokens)
         }
@@ -266,8 +990,8 @@ impl RecursiveDescentParser {
 
         for token_and_span in tokens_and_spans {
             match token_and_span.token {
-                Token::OpenParen => paren_count += 1,
-                Token::CloseParen => paren_count -= 1,
+                Token::OpenParen | Token::OpenBracket | Token::OpenBrace => paren_count += 1,
+                Token::CloseParen | Token::CloseBracket | Token::CloseBrace => paren_count -= 1,
                 _ => {}
             }
 
@@ -281,13 +1005,39 @@ impl RecursiveDescentParser {
         }
 
         // if we matched all parens, we're good
-        if paren_count != 0 {
-            Err(ParseError::MismatchedParens(tokens_and_spans[end_idx - 1].from.clone()))
+        if paren_count < 0 {
+            Err(ParseError::UnexpectedCloseParen(tokens_and_spans[0].span()))
+        } else if paren_count > 0 {
+            Err(ParseError::UnclosedParen(
+                tokens_and_spans[0].span().merge(&tokens_and_spans[end_idx - 1].span())
+            ))
         } else {
             Ok(&tokens_and_spans[0..end_idx])
         }
     }
 
+    fn slice_next_form<'a>(
+        &self,
+        tokens_and_spans: &'a [TokenAndSpan],
+        previous: &Position,
+    ) -> Result<&'a [TokenAndSpan], ParseError> {
+        match tokens_and_spans.first() {
+            None
+            | Some(TokenAndSpan {
+                token: Token::CloseParen | Token::CloseBracket | Token::CloseBrace,
+                ..
+            }) => Err(ParseError::UnexpectedEof(previous.clone())),
+            Some(TokenAndSpan { token, to, .. })
+                if has_reader_macro(&self.reader_macros, token) =>
+            {
+                // a reader macro applied to another reader macro
+                let rest = self.slice_next_form(&tokens_and_spans[1..], to)?;
+                Ok(&tokens_and_spans[..rest.len() + 1])
+            }
+            Some(_) => Self::slice_until_tokens_match(tokens_and_spans),
+        }
+    }
+
     fn find_tokens_within_brackets(
         tokens_and_spans: &[TokenAndSpan],
     ) -> Result<&[TokenAndSpan], ParseError> {
@@ -295,38 +1045,173 @@ impl RecursiveDescentParser {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    extern crate rstest;
-
-    use rstest::*;
-
-    use super::*;
-    use crate::tok::{Position, TokenAndSpan, TokenizerError};
+/// Which parens in a token stream belong together, by index into the stream.
+#[derive(Debug, PartialEq, Default)]
+pub struct BracketMatches {
+    /// `(open, close)` index pairs, ordered by the open paren
+    pub pairs: Vec<(usize, usize)>,
+    pub unmatched_open: Vec<usize>,
+    pub unmatched_close: Vec<usize>,
+}
 
-    struct MockyTokenizer {
-        returns: Vec<Result<TokenAndSpan, TokenizerError>>,
+impl BracketMatches {
+    /// The index of the paren matching the one at `index`, if any.
+    pub fn matching(&self, index: usize) -> Option<usize> {
+        self.pairs.iter().find_map(|&(open, close)| {
+            if index == open {
+                Some(close)
+            } else if index == close {
+                Some(open)
+            } else {
+                None
+            }
+        })
     }
+}
 
-    impl MockyTokenizer {
-        fn new(tokens_and_spans: Vec<TokenAndSpan>) -> MockyTokenizer {
-            MockyTokenizer {
-                returns: tokens_and_spans.into_iter().map(Result::Ok).rev().collect(),
+/// Pairs up every open paren, bracket or brace with its close, for things like bracket matching in editors.
+/// Parens that don't have a partner are reported separately rather than treated as errors.
+pub fn match_brackets(tokens_and_spans: &[TokenAndSpan]) -> BracketMatches {
+    let mut matches = BracketMatches::default();
+    let mut open_parens = vec![];
+
+    for (index, token_and_span) in tokens_and_spans.iter().enumerate() {
+        let opener = match token_and_span.token {
+            Token::OpenParen | Token::OpenBracket | Token::OpenBrace => {
+                open_parens.push(index);
+                continue;
+            }
+            Token::CloseParen => Token::OpenParen,
+            Token::CloseBracket => Token::OpenBracket,
+            Token::CloseBrace => Token::OpenBrace,
+            _ => continue,
+        };
+
+        // a close only pairs up with the innermost open of the same kind
+        match open_parens.last() {
+            Some(&open) if tokens_and_spans[open].token == opener => {
+                open_parens.pop();
+                matches.pairs.push((open, index));
             }
+            _ => matches.unmatched_close.push(index),
         }
+    }
 
-        fn new_with_zeros(tokens: Vec<Token>) -> MockyTokenizer {
-            Self::new(
-                tokens
-                    .into_iter()
-                    .map(|token| TokenAndSpan {
-                        token,
-                        from: Position {
+    matches.pairs.sort_unstable();
+    matches.unmatched_open = open_parens;
+    matches
+}
+
+/// A lower level view of parsing: rather than building `AST`s, report forms opening and closing
+/// and the atoms within them as tokens come off the tokenizer. Forms carry the bracket they open
+/// or close with, so consumers know what kind of form it is and where it is in the source.
+#[derive(Debug, PartialEq)]
+pub enum ParseEvent {
+    StartForm(TokenAndSpan),
+    Atom(TokenAndSpan),
+    EndForm(TokenAndSpan),
+}
+
+pub struct EventParser {
+    tokenizer: Box<dyn Tokenizer>,
+    // the opens of the forms we're inside of, innermost last, for matching up their closes
+    open_forms: Vec<TokenAndSpan>,
+    last_position: Position,
+}
+
+impl EventParser {
+    pub fn new(tokenizer: Box<dyn Tokenizer>) -> Self {
+        Self {
+            tokenizer,
+            open_forms: vec![],
+            last_position: Position {
+                line: 1,
+                column: 0,
+                position: 0,
+            },
+        }
+    }
+}
+
+impl Iterator for EventParser {
+    type Item = Result<ParseEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Result<ParseEvent, ParseError>> {
+        let token_and_span = loop {
+            match self.tokenizer.next() {
+                // whitespace and comments carry no meaning for the parser
+                Some(Ok(TokenAndSpan { token: Token::Whitespace(_) | Token::Comment(_), .. })) => {}
+                Some(Ok(token_and_span)) => break token_and_span,
+                Some(Err(err)) => return Some(Err(ParseError::from(err))),
+                None => {
+                    // report forms left open at the end of input once, by the innermost one
+                    let innermost = self.open_forms.pop()?;
+                    self.open_forms.clear();
+                    return Some(Err(ParseError::UnclosedParen(Span {
+                        from: innermost.from,
+                        to: self.last_position.clone(),
+                    })));
+                }
+            }
+        };
+        self.last_position = token_and_span.to.clone();
+
+        let opener = match token_and_span.token {
+            Token::OpenParen | Token::OpenBracket | Token::OpenBrace => {
+                self.open_forms.push(token_and_span.clone());
+                return Some(Ok(ParseEvent::StartForm(token_and_span)));
+            }
+            Token::CloseParen => Token::OpenParen,
+            Token::CloseBracket => Token::OpenBracket,
+            Token::CloseBrace => Token::OpenBrace,
+            _ => return Some(Ok(ParseEvent::Atom(token_and_span))),
+        };
+
+        // a close only ends the innermost form, and only if it's the same kind
+        match self.open_forms.last() {
+            Some(open) if open.token == opener => {
+                self.open_forms.pop();
+                Some(Ok(ParseEvent::EndForm(token_and_span)))
+            }
+            _ => Some(Err(ParseError::UnexpectedCloseParen(token_and_span.span()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rstest;
+
+    use rstest::*;
+
+    use super::*;
+    use crate::tok::{GreedyTokenizer, Position, TokenAndSpan, TokenizerError};
+
+    struct MockyTokenizer {
+        returns: Vec<Result<TokenAndSpan, TokenizerError>>,
+    }
+
+    impl MockyTokenizer {
+        fn new(tokens_and_spans: Vec<TokenAndSpan>) -> MockyTokenizer {
+            MockyTokenizer {
+                returns: tokens_and_spans.into_iter().map(Result::Ok).rev().collect(),
+            }
+        }
+
+        fn new_with_zeros(tokens: Vec<Token>) -> MockyTokenizer {
+            Self::new(
+                tokens
+                    .into_iter()
+                    .map(|token| TokenAndSpan {
+                        token,
+                        from: Position {
                             line: 1,
+                            column: 0,
                             position: 0,
                         },
                         to: Position {
                             line: 1,
+                            column: 1,
                             position: 1,
                         },
                     })
@@ -357,6 +1242,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_throws_an_error_for_forms_nested_too_deeply() {
+        let mut parser =
+            RecursiveDescentParser::from_source("(a (b (c))) (a (b (c (d)))) ['('x)] (a)")
+                .with_max_depth(3);
+        assert!(parser.next_expression().unwrap().is_some());
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::NestingTooDeep(Position { line: 1, column: 21, position: 21 })
+        );
+        // quotes nest the form they apply to
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::NestingTooDeep(Position { line: 1, column: 31, position: 31 })
+        );
+        // the rest of a form that's too deep gets skipped
+        assert!(parser.next_expression().unwrap().is_some());
+        assert!(parser.next_expression().unwrap().is_none());
+
+        // right up to the default depth is fine, and way past it errors rather than overflowing
+        let nested = |depth| format!("{}x{}", "(f ".repeat(depth), ")".repeat(depth));
+        let source = format!("{} {}", nested(DEFAULT_MAX_DEPTH), nested(100_000));
+        let mut parser = RecursiveDescentParser::from_source(&source);
+        assert!(parser.next_expression().unwrap().is_some());
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::NestingTooDeep(Position {
+                line: 1,
+                column: 4 * DEFAULT_MAX_DEPTH + 2 + 3 * DEFAULT_MAX_DEPTH,
+                position: 4 * DEFAULT_MAX_DEPTH + 2 + 3 * DEFAULT_MAX_DEPTH,
+            })
+        );
+    }
+
+    #[test]
+    fn it_recovers_from_malformed_expressions() {
+        let mut parser = RecursiveDescentParser::from_source("(def) (f 1) (g 1.2.3 (h)) 2");
+        assert_eq!(
+            parser.next_expression_recovering(),
+            (
+                Some(AST::EvaluateExpr {
+                    callee: String::from("f"),
+                    args: vec![AST::NumberExpr(1.0)]
+                }),
+                vec![ParseError::UnexpectedEof(Position { line: 1, column: 3, position: 3 })]
+            )
+        );
+
+        // a bad token only costs the form it's in
+        let (expression, errors) = parser.next_expression_recovering();
+        assert_eq!(expression, Some(AST::NumberExpr(2.0)));
+        assert!(matches!(
+            &errors[..],
+            [ParseError::TokenizerError(TokenizerError::ReadError { .. })]
+        ));
+
+        assert_eq!(parser.next_expression_recovering(), (None, vec![]));
+    }
+
     #[test]
     fn it_wraps_tokenizer_error_with_parse_error() {
         let tok = MockyTokenizer::new_with_errors(
@@ -365,10 +1309,12 @@ mod tests {
                 message: String::from("who dat"),
                 from: Position {
                     line: 1,
+                    column: 0,
                     position: 0,
                 },
                 to: Position {
                     line: 1,
+                    column: 0,
                     position: 0,
                 },
             },
@@ -384,6 +1330,7 @@ mod tests {
                     from,
                     Position {
                         line: 1,
+                        column: 0,
                         position: 0
                     }
                 );
@@ -391,6 +1338,7 @@ mod tests {
                     to,
                     Position {
                         line: 1,
+                        column: 0,
                         position: 0
                     }
                 );
@@ -415,8 +1363,8 @@ mod tests {
         assert_eq!(parser.next_expression(), Err(ParseError::UnexpectedTokenError {
             expected: None,
             found: Some(Token::Unknown('.')),
-            from: Position { line: 1, position: 0 },
-            to: Position { line: 1, position: 1 },
+            from: Position { line: 1, column: 0, position: 0 },
+            to: Position { line: 1, column: 1, position: 1 },
         }));
     }
 
@@ -425,20 +1373,35 @@ mod tests {
     #[case(Token::Number(-1.0), AST::NumberExpr(-1.0))]
     #[case(Token::Number(0.0), AST::NumberExpr(0.0))]
     #[case(Token::Number(188.0), AST::NumberExpr(188.0))]
+    // boolean bois
+    #[case(Token::Bool(true), AST::BoolExpr(true))]
+    #[case(Token::Bool(false), AST::BoolExpr(false))]
+    // nothing boi
+    #[case(Token::Nil, AST::NilExpr)]
     // string bois
     #[case(
         Token::Identifier(String::from("something")),
         AST::VariableExpr(String::from("something"))
     )]
+    #[case(
+        Token::String(String::from("some thing")),
+        AST::StringExpr(String::from("some thing"))
+    )]
+    #[case(
+        Token::Keyword(String::from("some-thing")),
+        AST::KeywordExpr(String::from("some-thing"))
+    )]
     fn it_parses_leaf_tokens(#[case] token: Token, #[case] expr: AST) {
         let tok = MockyTokenizer::new(vec![TokenAndSpan {
             token,
             from: Position {
                 line: 1,
+                column: 0,
                 position: 0,
             },
             to: Position {
                 line: 1,
+                column: 1,
                 position: 1,
             },
         }]);
@@ -447,6 +1410,24 @@ mod tests {
         assert_eq!(*parser.next_expression().unwrap().unwrap(), expr);
     }
 
+    #[test]
+    fn it_parses_bools_from_source() {
+        let tok = GreedyTokenizer::new(&b"true (something false truething)"[..]).unwrap();
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(*parser.next_expression().unwrap().unwrap(), AST::BoolExpr(true));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("something"),
+                args: vec![
+                    AST::BoolExpr(false),
+                    AST::VariableExpr(String::from("truething"))
+                ]
+            }
+        );
+    }
+
     #[test]
     fn it_parses_no_args_expressions() {
         let tok = MockyTokenizer::new_with_zeros(vec![
@@ -475,9 +1456,9 @@ mod tests {
         assert_eq!(
             parser.next_expression().unwrap_err(),
             ParseError::UnexpectedExpressionError {
-                expected: Some(AST::VariableExpr(String::from("_"))),
-                found: Some(AST::NumberExpr(1.0)),
-                position: Position { line: 1, position: 0 }
+                expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                found: Some(Box::new(AST::NumberExpr(1.0))),
+                position: Position { line: 1, column: 0, position: 0 }
             }
         );
     }
@@ -535,144 +1516,1335 @@ mod tests {
     }
 
     #[test]
-    fn it_returns_multiple_statements_as_separate_expressions() {
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Identifier(String::from("something")),
-            Token::Number(1.0),
-            Token::CloseParen,
-            Token::OpenParen,
-            Token::Identifier(String::from("something_else")),
-            Token::Number(2.0),
-            Token::CloseParen,
-        ]);
+    fn it_parses_expressions_with_args_after_nested_expressions() {
+        let tok = GreedyTokenizer::new("(something (first 1) (second 2) 3)".as_bytes()).unwrap();
 
         let mut parser = RecursiveDescentParser::new(Box::new(tok));
         assert_eq!(
             *parser.next_expression().unwrap().unwrap(),
             AST::EvaluateExpr {
                 callee: String::from("something"),
-                args: vec![AST::NumberExpr(1.0),]
-            },
-        );
-        assert_eq!(
-            *parser.next_expression().unwrap().unwrap(),
-            AST::EvaluateExpr {
-                callee: String::from("something_else"),
-                args: vec![AST::NumberExpr(2.0)]
+                args: vec![
+                    AST::EvaluateExpr {
+                        callee: String::from("first"),
+                        args: vec![AST::NumberExpr(1.0)]
+                    },
+                    AST::EvaluateExpr {
+                        callee: String::from("second"),
+                        args: vec![AST::NumberExpr(2.0)]
+                    },
+                    AST::NumberExpr(3.0),
+                ]
             }
         );
     }
 
     #[test]
-    fn it_parses_def_statements_into_assignment_operation() {
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Def,
-            Token::Identifier(String::from("whodat")),
-            Token::Number(1.0),
-            Token::CloseParen,
-        ]);
+    fn it_expands_threading_macros() {
+        let mut parser = RecursiveDescentParser::from_source(
+            "(-> x (f a) (g b) h) (h (g (f x a) b)) (->> x (f a) (g b) h) (h (g b (f a x)))",
+        );
+        assert_eq!(
+            parser.next_expression().unwrap().unwrap(),
+            parser.next_expression().unwrap().unwrap()
+        );
+        assert_eq!(
+            parser.next_expression().unwrap().unwrap(),
+            parser.next_expression().unwrap().unwrap()
+        );
 
-        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        // threading into nested forms only touches the outer form
+        let mut parser =
+            RecursiveDescentParser::from_source("(-> 1 (+ (* 2 3))) (+ 1 (* 2 3))");
         assert_eq!(
-            *parser.next_expression().unwrap().unwrap(),
-            AST::EvaluateExpr {
-                callee: String::from("__assign"),
-                args: vec![
-                    AST::VariableExpr(String::from("whodat")),
-                    AST::NumberExpr(1.0),
-                ]
-            },
+            parser.next_expression().unwrap().unwrap(),
+            parser.next_expression().unwrap().unwrap()
         );
 
-        // it throws an error if i use a non-identifier type as name
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Def,
-            Token::Fn,
-            Token::Number(1.0),
-            Token::CloseParen,
-        ]);
+        // calls to forms other than a name get threaded into too
+        let mut parser = RecursiveDescentParser::from_source(
+            "(-> 1 ((fn (x) (x)))) ((fn (x) (x)) 1) \
+             (-> 1 ((fn (x y) (x)) 2)) ((fn (x y) (x)) 1 2) \
+             (->> 1 ((fn (x y) (x)) 2)) ((fn (x y) (x)) 2 1)",
+        );
+        for _ in 0..3 {
+            assert_eq!(
+                parser.next_expression().unwrap().unwrap(),
+                parser.next_expression().unwrap().unwrap()
+            );
+        }
+    }
 
-        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+    #[test]
+    fn it_keeps_nil_safe_threading_macros_for_the_evaluator() {
+        let mut parser = RecursiveDescentParser::from_source("(some->> x (f a) h) (some-> x 1)");
+        assert_eq!(
+            parser.next_expression().unwrap().unwrap(),
+            Box::new(AST::SomeThreadExpr {
+                initial: Box::new(AST::VariableExpr(String::from("x"))),
+                steps: vec![
+                    AST::EvaluateExpr {
+                        callee: String::from("f"),
+                        args: vec![AST::VariableExpr(String::from("a"))]
+                    },
+                    AST::VariableExpr(String::from("h")),
+                ],
+                thread_last: true,
+            })
+        );
         assert_eq!(
             parser.next_expression().unwrap_err(),
-            ParseError::UnexpectedTokenError {
-                expected: Some(Token::Identifier(String::from("_"))),
-                found: Some(Token::Fn),
-                from: Position { line: 1, position: 0 },
-                to: Position { line: 1, position: 1 },
+            ParseError::UnexpectedExpressionError {
+                expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                found: Some(Box::new(AST::NumberExpr(1.0))),
+                position: Position { line: 1, column: 21, position: 21 }
             }
         );
+    }
 
-        // it throws an error if we provide too many args
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Def,
-            Token::Identifier(String::from("too_many_args")),
-            Token::Number(1.0),
-            Token::Number(2.0),
-            Token::CloseParen,
-        ]);
-
-        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+    #[test]
+    fn it_throws_an_error_for_bad_threading_macros() {
+        let mut parser = RecursiveDescentParser::from_source("(-> x) (->> x 1)");
         assert_eq!(
             parser.next_expression().unwrap_err(),
             ParseError::UnexpectedExpressionError {
                 expected: None,
-                found: Some(AST::NumberExpr(2.0)),
-                position: Position { line: 1, position: 0 }
+                found: Some(Box::new(AST::VariableExpr(String::from("x")))),
+                position: Position { line: 1, column: 1, position: 1 }
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedExpressionError {
+                expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                found: Some(Box::new(AST::NumberExpr(1.0))),
+                position: Position { line: 1, column: 8, position: 8 }
             }
         );
     }
 
     #[test]
-    fn it_parses_a_function_definition_into_a_function() {
-        // function without args
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Fn,
-            Token::OpenParen,
-            Token::CloseParen,
-            Token::OpenParen,
-            Token::Identifier(String::from("contents")),
-            Token::CloseParen,
-            Token::CloseParen,
-        ]);
+    fn it_parses_do_blocks() {
+        let mut parser = RecursiveDescentParser::from_source("(do (def x 1) (f x)) (do)");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::DoExpr(vec![
+                AST::EvaluateExpr {
+                    callee: String::from("__assign"),
+                    args: vec![
+                        AST::VariableExpr(String::from("x")),
+                        AST::NumberExpr(1.0)
+                    ]
+                },
+                AST::EvaluateExpr {
+                    callee: String::from("f"),
+                    args: vec![AST::VariableExpr(String::from("x"))]
+                },
+            ])
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::DoExpr(vec![])
+        );
+    }
 
-        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+    #[test]
+    fn it_parses_ns_declarations() {
+        let mut parser = RecursiveDescentParser::from_source("(ns app.core) (ns)");
         assert_eq!(
             *parser.next_expression().unwrap().unwrap(),
-            AST::FunctionExpr {
-                parameters: vec![],
-                statements: vec![AST::VariableExpr(String::from("contents"))]
-            },
+            AST::NsExpr(String::from("app.core"))
+        );
+        assert_eq!(
+            parser.next_expression(),
+            Err(ParseError::UnexpectedEof(Position { line: 1, column: 16, position: 16 }))
         );
+    }
 
-        // function with args
-        let tok = MockyTokenizer::new_with_zeros(vec![
-            Token::OpenParen,
-            Token::Fn,
-            Token::OpenParen,
-            Token::Identifier(String::from("arg1")),
-            Token::Identifier(String::from("arg2")),
-            Token::CloseParen,
-            Token::OpenParen,
-            Token::Identifier(String::from("contents")),
-            Token::CloseParen,
-            Token::CloseParen,
-        ]);
+    #[test]
+    fn it_parses_quoted_forms() {
+        let quoted_foo = AST::QuoteExpr(Box::new(AST::VariableExpr(String::from("foo"))));
+        let mut parser = RecursiveDescentParser::from_source("'foo (quote foo)");
+        assert_eq!(*parser.next_expression().unwrap().unwrap(), quoted_foo);
+        assert_eq!(*parser.next_expression().unwrap().unwrap(), quoted_foo);
+
+        // a quote takes the one whole form after it, even another quote
+        let mut parser = RecursiveDescentParser::from_source("(f '[1 x] ''y)");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("f"),
+                args: vec![
+                    AST::QuoteExpr(Box::new(AST::ListExpr(vec![
+                        AST::NumberExpr(1.0),
+                        AST::VariableExpr(String::from("x")),
+                    ]))),
+                    AST::QuoteExpr(Box::new(AST::QuoteExpr(Box::new(AST::VariableExpr(
+                        String::from("y")
+                    ))))),
+                ]
+            }
+        );
 
-        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        // quote is only a special form at the head of a paren form, around exactly one form
+        for source in ["(f quote x)", "[quote x]", "quote x"] {
+            let mut parser = RecursiveDescentParser::from_source(source);
+            assert!(
+                matches!(
+                    parser.next_expression(),
+                    Err(ParseError::UnexpectedTokenError { found: Some(Token::QuoteForm), .. })
+                ),
+                "{}",
+                source
+            );
+        }
+        let mut parser = RecursiveDescentParser::from_source("(quote x y)");
+        assert_eq!(
+            parser.next_expression(),
+            Err(ParseError::UnexpectedTokenError {
+                expected: Some(Token::CloseParen),
+                found: Some(Token::Identifier(String::from("y"))),
+                from: Position { line: 1, column: 9, position: 9 },
+                to: Position { line: 1, column: 9, position: 9 },
+            })
+        );
+
+        // and parens around a quoted form call it, rather than disappearing
+        let mut parser = RecursiveDescentParser::from_source("('x)");
+        assert!(matches!(
+            parser.next_expression(),
+            Err(ParseError::UnexpectedExpressionError { found: Some(found), .. })
+                if matches!(*found, AST::QuoteExpr(_))
+        ));
+
+        // but there has to be a form to quote
+        let mut parser = RecursiveDescentParser::from_source("(quote)");
+        assert_eq!(
+            parser.next_expression(),
+            Err(ParseError::UnexpectedEof(Position { line: 1, column: 5, position: 5 }))
+        );
+        let mut parser = RecursiveDescentParser::from_source("(f ')");
+        assert_eq!(
+            parser.next_expression(),
+            Err(ParseError::UnexpectedEof(Position { line: 1, column: 3, position: 3 }))
+        );
+    }
+
+    #[test]
+    fn it_parses_cond_expressions() {
+        let mut parser = RecursiveDescentParser::from_source(
+            "(cond (< x 0) \"negative\" :else \"not\") (cond 1 2 3)",
+        );
         assert_eq!(
             *parser.next_expression().unwrap().unwrap(),
-            AST::FunctionExpr {
-                parameters: vec![String::from("arg1"), String::from("arg2")],
-                statements: vec![AST::VariableExpr(String::from("contents"))]
-            },
+            AST::CondExpr(vec![
+                (
+                    AST::EvaluateExpr {
+                        callee: String::from("<"),
+                        args: vec![AST::VariableExpr(String::from("x")), AST::NumberExpr(0.0)]
+                    },
+                    AST::StringExpr(String::from("negative"))
+                ),
+                (
+                    AST::KeywordExpr(String::from("else")),
+                    AST::StringExpr(String::from("not"))
+                ),
+            ])
         );
 
-        // TODO: handle errors
+        // every test needs a result
+        assert_eq!(
+            parser.next_expression(),
+            Err(ParseError::OddNumberOfCondForms(Position { line: 1, column: 49, position: 49 }))
+        );
+    }
+
+    #[test]
+    fn it_parses_let_expressions() {
+        let mut parser = RecursiveDescentParser::from_source("(let [x 1 y 2] (+ x y))");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::LetExpr {
+                bindings: vec![
+                    (String::from("x"), AST::NumberExpr(1.0)),
+                    (String::from("y"), AST::NumberExpr(2.0)),
+                ],
+                body: vec![AST::EvaluateExpr {
+                    callee: String::from("+"),
+                    args: vec![
+                        AST::VariableExpr(String::from("x")),
+                        AST::VariableExpr(String::from("y")),
+                    ]
+                }]
+            }
+        );
+
+        // bindings can be expressions, and the body can be empty
+        let mut parser = RecursiveDescentParser::from_source("(let [x (f 1)]) (g (let [] 1) 2)");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::LetExpr {
+                bindings: vec![(
+                    String::from("x"),
+                    AST::EvaluateExpr {
+                        callee: String::from("f"),
+                        args: vec![AST::NumberExpr(1.0)]
+                    }
+                )],
+                body: vec![]
+            }
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("g"),
+                args: vec![
+                    AST::LetExpr {
+                        bindings: vec![],
+                        body: vec![AST::NumberExpr(1.0)]
+                    },
+                    AST::NumberExpr(2.0),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_bad_let_bindings() {
+        let mut parser =
+            RecursiveDescentParser::from_source("(let [x 1 y] x) (let [1 x] x) (let x 1) (let)");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::OddNumberOfBindings(Position { line: 1, column: 11, position: 11 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedExpressionError {
+                expected: Some(Box::new(AST::VariableExpr(String::from("_")))),
+                found: Some(Box::new(AST::NumberExpr(1.0))),
+                position: Position { line: 1, column: 21, position: 21 }
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: Some(Token::OpenBracket),
+                found: Some(Token::Identifier(String::from("x"))),
+                from: Position { line: 1, column: 35, position: 35 },
+                to: Position { line: 1, column: 35, position: 35 },
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 43, position: 43 })
+        );
+    }
+
+    #[test]
+    fn it_parses_maps() {
+        let mut parser = RecursiveDescentParser::from_source("{:a 1 :b (f x)} {} {:a 1 :b}");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::MapExpr(vec![
+                (AST::KeywordExpr(String::from("a")), AST::NumberExpr(1.0)),
+                (
+                    AST::KeywordExpr(String::from("b")),
+                    AST::EvaluateExpr {
+                        callee: String::from("f"),
+                        args: vec![AST::VariableExpr(String::from("x"))]
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::MapExpr(vec![])
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::OddNumberOfMapForms(Position { line: 1, column: 27, position: 27 })
+        );
+    }
+
+    #[test]
+    fn it_parses_keywords_at_the_front_of_vectors() {
+        let mut parser = RecursiveDescentParser::from_source("[:name \"lispy\"]");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ListExpr(vec![
+                AST::KeywordExpr(String::from("name")),
+                AST::StringExpr(String::from("lispy")),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_parses_vectors() {
+        let mut parser = RecursiveDescentParser::from_source("[1 2 3] [] (f [x [y]])");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ListExpr(vec![
+                AST::NumberExpr(1.0),
+                AST::NumberExpr(2.0),
+                AST::NumberExpr(3.0),
+            ])
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ListExpr(vec![])
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("f"),
+                args: vec![AST::ListExpr(vec![
+                    AST::VariableExpr(String::from("x")),
+                    AST::ListExpr(vec![AST::VariableExpr(String::from("y"))]),
+                ])]
+            }
+        );
+        assert!(parser.next_expression().unwrap().is_none());
+
+        // brackets and parens can't close each other
+        let mut parser = RecursiveDescentParser::from_source("(f [1 2)]");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedCloseParen(Span {
+                from: Position { line: 1, column: 7, position: 7 },
+                to: Position { line: 1, column: 7, position: 7 },
+            })
+        );
+    }
+
+    #[test]
+    fn it_parses_all_expressions_at_once() {
+        let tok = GreedyTokenizer::new("(something 1) (something_else 2)".as_bytes()).unwrap();
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.parse_all().unwrap(),
+            vec![
+                AST::EvaluateExpr {
+                    callee: String::from("something"),
+                    args: vec![AST::NumberExpr(1.0)]
+                },
+                AST::EvaluateExpr {
+                    callee: String::from("something_else"),
+                    args: vec![AST::NumberExpr(2.0)]
+                },
+            ]
+        );
+        assert_eq!(parser.parse_all().unwrap(), vec![]);
+
+        // the first error stops parsing
+        let tok = GreedyTokenizer::new("(something 1) (def) (something_else 2)".as_bytes()).unwrap();
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.parse_all().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 17, position: 17 })
+        );
+    }
+
+    #[test]
+    fn it_parses_calls_to_expressions_into_apply_expressions() {
+        let mut parser = RecursiveDescentParser::from_source("((fn (x) (x)) 5) ((f 1) 2) ((mk))");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ApplyExpr {
+                function: Box::new(AST::FunctionExpr {
+                    parameters: vec![String::from("x")],
+                    rest_parameter: None,
+                    statements: vec![AST::VariableExpr(String::from("x"))]
+                }),
+                args: vec![AST::NumberExpr(5.0)]
+            }
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ApplyExpr {
+                function: Box::new(AST::EvaluateExpr {
+                    callee: String::from("f"),
+                    args: vec![AST::NumberExpr(1.0)]
+                }),
+                args: vec![AST::NumberExpr(2.0)]
+            }
+        );
+
+        // which still calls the result when there aren't any args, rather than being dropped
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::ApplyExpr {
+                function: Box::new(AST::EvaluateExpr {
+                    callee: String::from("mk"),
+                    args: vec![]
+                }),
+                args: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn it_returns_multiple_statements_as_separate_expressions() {
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Identifier(String::from("something")),
+            Token::Number(1.0),
+            Token::CloseParen,
+            Token::OpenParen,
+            Token::Identifier(String::from("something_else")),
+            Token::Number(2.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("something"),
+                args: vec![AST::NumberExpr(1.0),]
+            },
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("something_else"),
+                args: vec![AST::NumberExpr(2.0)]
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_def_statements_into_assignment_operation() {
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Def,
+            Token::Identifier(String::from("whodat")),
+            Token::Number(1.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("__assign"),
+                args: vec![
+                    AST::VariableExpr(String::from("whodat")),
+                    AST::NumberExpr(1.0),
+                ]
+            },
+        );
+
+        // nil is a perfectly fine value
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Def,
+            Token::Identifier(String::from("x")),
+            Token::Nil,
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("__assign"),
+                args: vec![AST::VariableExpr(String::from("x")), AST::NilExpr]
+            },
+        );
+
+        // it throws an error if i use a non-identifier type as name
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Def,
+            Token::String(String::from("x")),
+            Token::Number(1.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: Some(Token::Identifier(String::from("_"))),
+                found: Some(Token::String(String::from("x"))),
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 1, position: 1 },
+            }
+        );
+
+        // it throws an error if we provide too many args
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Def,
+            Token::Identifier(String::from("too_many_args")),
+            Token::Number(1.0),
+            Token::Number(2.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: Some(Box::new(AST::NumberExpr(2.0))),
+                position: Position { line: 1, column: 0, position: 0 }
+            }
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_reserved_keywords_used_as_names() {
+        let mut parser =
+            RecursiveDescentParser::from_source("(def fn 1) (def def 1) (fn (a if) (a))");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::ReservedKeywordAsName {
+                keyword: String::from("fn"),
+                position: Position { line: 1, column: 5, position: 5 }
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::ReservedKeywordAsName {
+                keyword: String::from("def"),
+                position: Position { line: 1, column: 16, position: 16 }
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::ReservedKeywordAsName {
+                keyword: String::from("if"),
+                position: Position { line: 1, column: 30, position: 30 }
+            }
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_def_statements_used_as_values() {
+        let mut parser =
+            RecursiveDescentParser::from_source("(def x (def y 1))\n(def f (defn g () (1)))");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 8, position: 8 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 2, column: 8, position: 26 })
+        );
+    }
+
+    #[test]
+    fn it_only_allows_def_statements_where_statements_go() {
+        let mut parser = RecursiveDescentParser::from_source(
+            "(+ (def x 1) 2) [(defn f (a) (a))] (if true (def x 1)) (foo def x 1) [1 defn f (a) a]",
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 4, position: 4 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 18, position: 18 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 45, position: 45 })
+        );
+
+        // even when it isn't a paren form of its own
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 60, position: 60 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::DefNotAllowedHere(Position { line: 1, column: 72, position: 72 })
+        );
+
+        // top level defs are fine, and so are ones in a do or a body of statements
+        let mut parser = RecursiveDescentParser::from_source(
+            "(def x 1) (do (def y 2)) (let [] (def z 3)) (fn () ((def w 4) w))",
+        );
+        for _ in 0..4 {
+            assert!(parser.next_expression().unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn it_throws_an_error_for_def_statements_that_end_early() {
+        let tok = GreedyTokenizer::new("(def) (def x) def".as_bytes()).unwrap();
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 3, position: 3 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 11, position: 11 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 16, position: 16 })
+        );
+    }
+
+    #[test]
+    fn it_parses_if_expressions() {
+        // with an else branch
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::If,
+            Token::Bool(true),
+            Token::Number(1.0),
+            Token::OpenParen,
+            Token::Identifier(String::from("something")),
+            Token::CloseParen,
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::IfExpr {
+                condition: Box::new(AST::BoolExpr(true)),
+                then_branch: Box::new(AST::NumberExpr(1.0)),
+                else_branch: Some(Box::new(AST::EvaluateExpr {
+                    callee: String::from("something"),
+                    args: vec![]
+                })),
+            },
+        );
+
+        // without an else branch
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::If,
+            Token::Identifier(String::from("x")),
+            Token::Number(1.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::IfExpr {
+                condition: Box::new(AST::VariableExpr(String::from("x"))),
+                then_branch: Box::new(AST::NumberExpr(1.0)),
+                else_branch: None,
+            },
+        );
+    }
+
+    #[test]
+    fn it_throws_an_error_for_if_expressions_with_wrong_number_of_branches() {
+        // too few
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::If,
+            Token::Bool(true),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: None,
+                position: Position { line: 1, column: 0, position: 0 }
+            }
+        );
+
+        // too many
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::If,
+            Token::Bool(true),
+            Token::Number(1.0),
+            Token::Number(2.0),
+            Token::Number(3.0),
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedExpressionError {
+                expected: None,
+                found: Some(Box::new(AST::NumberExpr(3.0))),
+                position: Position { line: 1, column: 0, position: 0 }
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_a_function_definition_into_a_function() {
+        // function without args
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Fn,
+            Token::OpenParen,
+            Token::CloseParen,
+            Token::OpenParen,
+            Token::Identifier(String::from("contents")),
+            Token::CloseParen,
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::FunctionExpr {
+                parameters: vec![],
+                rest_parameter: None,
+                statements: vec![AST::VariableExpr(String::from("contents"))]
+            },
+        );
+
+        // function with args
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Fn,
+            Token::OpenParen,
+            Token::Identifier(String::from("arg1")),
+            Token::Identifier(String::from("arg2")),
+            Token::CloseParen,
+            Token::OpenParen,
+            Token::Identifier(String::from("contents")),
+            Token::CloseParen,
+            Token::CloseParen,
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::FunctionExpr {
+                parameters: vec![String::from("arg1"), String::from("arg2")],
+                rest_parameter: None,
+                statements: vec![AST::VariableExpr(String::from("contents"))]
+            },
+        );
+
+        // TODO: handle errors
+    }
+
+    #[test]
+    fn it_parses_rest_parameters() {
+        let mut parser =
+            RecursiveDescentParser::from_source("(fn (x & rest) (rest)) (fn (x &) (x)) (fn (& a b) (a))");
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::FunctionExpr {
+                parameters: vec![String::from("x")],
+                rest_parameter: Some(String::from("rest")),
+                statements: vec![AST::VariableExpr(String::from("rest"))]
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: Some(Token::Identifier(String::from("_"))),
+                found: None,
+                from: Position { line: 1, column: 30, position: 30 },
+                to: Position { line: 1, column: 30, position: 30 },
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: Some(Token::CloseParen),
+                found: Some(Token::Identifier(String::from("b"))),
+                from: Position { line: 1, column: 47, position: 47 },
+                to: Position { line: 1, column: 47, position: 47 },
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_defn_statements_into_an_assigned_function() {
+        let mut parser = RecursiveDescentParser::from_source("(defn square (x) ((* x x)))");
+        let expected = AST::EvaluateExpr {
+            callee: String::from("__assign"),
+            args: vec![
+                AST::VariableExpr(String::from("square")),
+                AST::FunctionExpr {
+                    parameters: vec![String::from("x")],
+                    rest_parameter: None,
+                    statements: vec![AST::EvaluateExpr {
+                        callee: String::from("*"),
+                        args: vec![
+                            AST::VariableExpr(String::from("x")),
+                            AST::VariableExpr(String::from("x")),
+                        ],
+                    }],
+                },
+            ],
+        };
+        assert_eq!(*parser.next_expression().unwrap().unwrap(), expected);
+
+        // it's the same thing as a def of a fn
+        let mut parser = RecursiveDescentParser::from_source(
+            "(defn square (x) (* x x)) (def square (fn (x) (* x x)))",
+        );
+        assert_eq!(
+            parser.next_expression().unwrap().unwrap(),
+            parser.next_expression().unwrap().unwrap()
+        );
+
+        // and errors the same way too
+        let mut parser = RecursiveDescentParser::from_source("(defn) (defn 1 (x) (x)) (defn f (x))");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 4, position: 4 })
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: Some(Token::Identifier(String::from("_"))),
+                found: Some(Token::Number(1.0)),
+                from: Position { line: 1, column: 13, position: 13 },
+                to: Position { line: 1, column: 13, position: 13 },
+            }
+        );
+        assert_eq!(parser.next_expression().unwrap_err(), ParseError::FunctionNeedsABody);
+    }
+
+    #[test]
+    fn it_throws_an_error_for_fn_forms_that_end_early() {
+        let tok = GreedyTokenizer::new("(fn ()) (fn (a b)) fn".as_bytes()).unwrap();
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(parser.next_expression().unwrap_err(), ParseError::FunctionNeedsABody);
+        assert_eq!(parser.next_expression().unwrap_err(), ParseError::FunctionNeedsABody);
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 20, position: 20 })
+        );
+
+        // cut off in the middle of the parameter list
+        let tokens: Vec<TokenAndSpan> = GreedyTokenizer::new("(fn (a b".as_bytes())
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            parser.recursively_evaluate(&tokens).unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 7, position: 7 })
+        );
+    }
+
+    #[test]
+    fn it_applies_registered_reader_macros() {
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::Unknown('@'),
+            Token::OpenParen,
+            Token::Identifier(String::from("something")),
+            Token::Number(1.0),
+            Token::CloseParen,
+            Token::Unknown('@'),
+            Token::Unknown('@'),
+            Token::Identifier(String::from("x")),
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        parser.register_reader_macro('@', |form| AST::EvaluateExpr {
+            callee: String::from("debug"),
+            args: vec![form],
+        });
+
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("debug"),
+                args: vec![AST::EvaluateExpr {
+                    callee: String::from("something"),
+                    args: vec![AST::NumberExpr(1.0)]
+                }]
+            }
+        );
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("debug"),
+                args: vec![AST::EvaluateExpr {
+                    callee: String::from("debug"),
+                    args: vec![AST::VariableExpr(String::from("x"))]
+                }]
+            }
+        );
+
+        // unregistered characters are still rejected, and a dangling macro needs a form
+        let tok = MockyTokenizer::new_with_zeros(vec![Token::Unknown('`'), Token::Unknown('@')]);
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        parser.register_reader_macro('@', |form| form);
+
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedTokenError {
+                expected: None,
+                found: Some(Token::Unknown('`')),
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 1, position: 1 },
+            }
+        );
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedEof(Position { line: 1, column: 1, position: 1 })
+        );
+
+        // the built in ' is a reader macro like any other, so it can be replaced too
+        let mut parser = RecursiveDescentParser::from_source("'x");
+        parser.register_reader_macro('\'', |form| AST::EvaluateExpr {
+            callee: String::from("debug"),
+            args: vec![form],
+        });
+        assert_eq!(
+            *parser.next_expression().unwrap().unwrap(),
+            AST::EvaluateExpr {
+                callee: String::from("debug"),
+                args: vec![AST::VariableExpr(String::from("x"))]
+            }
+        );
+    }
+
+    #[test]
+    fn it_emits_parse_events_without_building_asts() {
+        let tok = GreedyTokenizer::new(&b"(def x 1)"[..]).unwrap();
+        let events = EventParser::new(Box::new(tok))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                ParseEvent::StartForm(TokenAndSpan {
+                    token: Token::OpenParen,
+                    from: Position { line: 1, column: 0, position: 0 },
+                    to: Position { line: 1, column: 0, position: 0 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Def,
+                    from: Position { line: 1, column: 1, position: 1 },
+                    to: Position { line: 1, column: 3, position: 3 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Identifier(String::from("x")),
+                    from: Position { line: 1, column: 5, position: 5 },
+                    to: Position { line: 1, column: 5, position: 5 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Number(1.0),
+                    from: Position { line: 1, column: 7, position: 7 },
+                    to: Position { line: 1, column: 7, position: 7 },
+                }),
+                ParseEvent::EndForm(TokenAndSpan {
+                    token: Token::CloseParen,
+                    from: Position { line: 1, column: 8, position: 8 },
+                    to: Position { line: 1, column: 8, position: 8 },
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_unbalanced_forms_as_parse_events() {
+        let tok = MockyTokenizer::new_with_zeros(vec![Token::CloseParen, Token::OpenParen]);
+        let mut events = EventParser::new(Box::new(tok));
+
+        assert_eq!(
+            events.next(),
+            Some(Err(ParseError::UnexpectedCloseParen(Span {
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 1, position: 1 },
+            })))
+        );
+        assert_eq!(
+            events.next(),
+            Some(Ok(ParseEvent::StartForm(TokenAndSpan {
+                token: Token::OpenParen,
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 1, position: 1 },
+            })))
+        );
+        assert_eq!(
+            events.next(),
+            Some(Err(ParseError::UnclosedParen(Span {
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 1, position: 1 },
+            })))
+        );
+        assert_eq!(events.next(), None);
+    }
+
+    #[test]
+    fn it_emits_parse_events_for_every_kind_of_bracket() {
+        let tok = GreedyTokenizer::new(&b"[1 {:a 2}] ; c"[..]).unwrap().with_full_fidelity(true);
+        let events = EventParser::new(Box::new(tok))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // with no whitespace or comments among the atoms
+        assert_eq!(
+            events,
+            vec![
+                ParseEvent::StartForm(TokenAndSpan {
+                    token: Token::OpenBracket,
+                    from: Position { line: 1, column: 0, position: 0 },
+                    to: Position { line: 1, column: 0, position: 0 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Number(1.0),
+                    from: Position { line: 1, column: 1, position: 1 },
+                    to: Position { line: 1, column: 1, position: 1 },
+                }),
+                ParseEvent::StartForm(TokenAndSpan {
+                    token: Token::OpenBrace,
+                    from: Position { line: 1, column: 3, position: 3 },
+                    to: Position { line: 1, column: 3, position: 3 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Keyword(String::from("a")),
+                    from: Position { line: 1, column: 4, position: 4 },
+                    to: Position { line: 1, column: 5, position: 5 },
+                }),
+                ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Number(2.0),
+                    from: Position { line: 1, column: 7, position: 7 },
+                    to: Position { line: 1, column: 7, position: 7 },
+                }),
+                ParseEvent::EndForm(TokenAndSpan {
+                    token: Token::CloseBrace,
+                    from: Position { line: 1, column: 8, position: 8 },
+                    to: Position { line: 1, column: 8, position: 8 },
+                }),
+                ParseEvent::EndForm(TokenAndSpan {
+                    token: Token::CloseBracket,
+                    from: Position { line: 1, column: 9, position: 9 },
+                    to: Position { line: 1, column: 9, position: 9 },
+                }),
+            ]
+        );
+
+        // closes have to match the innermost open, and the innermost form left open is reported
+        let tok = GreedyTokenizer::new(&b"(1 2] [x"[..]).unwrap();
+        let events = EventParser::new(Box::new(tok)).collect::<Vec<_>>();
+        assert_eq!(
+            events[3..],
+            [
+                Err(ParseError::UnexpectedCloseParen(Span {
+                    from: Position { line: 1, column: 4, position: 4 },
+                    to: Position { line: 1, column: 4, position: 4 },
+                })),
+                Ok(ParseEvent::StartForm(TokenAndSpan {
+                    token: Token::OpenBracket,
+                    from: Position { line: 1, column: 6, position: 6 },
+                    to: Position { line: 1, column: 6, position: 6 },
+                })),
+                Ok(ParseEvent::Atom(TokenAndSpan {
+                    token: Token::Identifier(String::from("x")),
+                    from: Position { line: 1, column: 7, position: 7 },
+                    to: Position { line: 1, column: 7, position: 7 },
+                })),
+                Err(ParseError::UnclosedParen(Span {
+                    from: Position { line: 1, column: 6, position: 6 },
+                    to: Position { line: 1, column: 7, position: 7 },
+                })),
+            ]
+        );
+    }
+
+    fn tokens_and_spans(tokens: Vec<Token>) -> Vec<TokenAndSpan> {
+        MockyTokenizer::new_with_zeros(tokens)
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn it_matches_balanced_brackets() {
+        let tokens = tokens_and_spans(vec![
+            Token::OpenParen,
+            Token::Identifier(String::from("f")),
+            Token::OpenParen,
+            Token::CloseParen,
+            Token::OpenParen,
+            Token::Number(1.0),
+            Token::CloseParen,
+            Token::CloseParen,
+        ]);
+
+        let matches = match_brackets(&tokens);
+        assert_eq!(
+            matches,
+            BracketMatches {
+                pairs: vec![(0, 7), (2, 3), (4, 6)],
+                unmatched_open: vec![],
+                unmatched_close: vec![],
+            }
+        );
+        assert_eq!(matches.matching(0), Some(7));
+        assert_eq!(matches.matching(6), Some(4));
+        assert_eq!(matches.matching(1), None);
+    }
+
+    #[test]
+    fn it_only_matches_brackets_of_the_same_kind() {
+        let tokens = tokens_and_spans(vec![
+            Token::OpenParen,
+            Token::OpenBracket,
+            Token::CloseParen,
+            Token::CloseBracket,
+            Token::CloseParen,
+        ]);
+
+        assert_eq!(
+            match_brackets(&tokens),
+            BracketMatches {
+                pairs: vec![(0, 4), (1, 3)],
+                unmatched_open: vec![],
+                unmatched_close: vec![2],
+            }
+        );
+    }
+
+    #[test]
+    fn it_tells_unclosed_parens_from_unexpected_closes() {
+        let mut parser = RecursiveDescentParser::from_source("(foo");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnclosedParen(Span {
+                from: Position { line: 1, column: 0, position: 0 },
+                to: Position { line: 1, column: 3, position: 3 },
+            })
+        );
+
+        let mut parser = RecursiveDescentParser::from_source("foo)");
+        parser.next_expression().unwrap();
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedCloseParen(Span {
+                from: Position { line: 1, column: 3, position: 3 },
+                to: Position { line: 1, column: 3, position: 3 },
+            })
+        );
+
+        // the innermost paren left open is the one reported, up to where the input ran out
+        let mut parser = RecursiveDescentParser::from_source("(f\n (g 1) [x (h");
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnclosedParen(Span {
+                from: Position { line: 2, column: 10, position: 13 },
+                to: Position { line: 2, column: 11, position: 14 },
+            })
+        );
+
+        let mut parser = RecursiveDescentParser::from_source("1 ]");
+        parser.next_expression().unwrap();
+        assert_eq!(
+            parser.next_expression().unwrap_err(),
+            ParseError::UnexpectedCloseParen(Span {
+                from: Position { line: 1, column: 2, position: 2 },
+                to: Position { line: 1, column: 2, position: 2 },
+            })
+        );
+    }
+
+    #[test]
+    fn it_flags_unmatched_open_brackets() {
+        let tokens = tokens_and_spans(vec![
+            Token::OpenParen,
+            Token::OpenParen,
+            Token::Identifier(String::from("f")),
+            Token::CloseParen,
+        ]);
+
+        assert_eq!(
+            match_brackets(&tokens),
+            BracketMatches {
+                pairs: vec![(1, 3)],
+                unmatched_open: vec![0],
+                unmatched_close: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn it_flags_unmatched_close_brackets() {
+        let tokens = tokens_and_spans(vec![
+            Token::CloseParen,
+            Token::OpenParen,
+            Token::CloseParen,
+            Token::CloseParen,
+        ]);
+
+        assert_eq!(
+            match_brackets(&tokens),
+            BracketMatches {
+                pairs: vec![(1, 2)],
+                unmatched_open: vec![],
+                unmatched_close: vec![0, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn it_ignores_whitespace_and_comment_tokens() {
+        let source = &b"(def x ; the value\n  (something 1))"[..];
+        let tok = GreedyTokenizer::new(source).unwrap();
+        let expected = RecursiveDescentParser::new(Box::new(tok)).next_expression();
+
+        let tok = GreedyTokenizer::new(source).unwrap().with_full_fidelity(true);
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(parser.next_expression(), expected);
+        assert_eq!(parser.next_expression(), Ok(None));
+    }
+
+    #[test]
+    fn it_reports_how_many_tokens_an_expression_consumed() {
+        let tok = MockyTokenizer::new_with_zeros(vec![Token::Number(1.0), Token::Number(2.0)]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression_with_consumed().unwrap(),
+            (Some(Box::new(AST::NumberExpr(1.0))), 1)
+        );
+
+        let tok = MockyTokenizer::new_with_zeros(vec![
+            Token::OpenParen,
+            Token::Identifier(String::from("something")),
+            Token::Number(1.0),
+            Token::OpenParen,
+            Token::Identifier(String::from("something_else")),
+            Token::CloseParen,
+            Token::CloseParen,
+            Token::Number(2.0),
+        ]);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        let (_, consumed) = parser.next_expression_with_consumed().unwrap();
+        assert_eq!(consumed, 7);
+
+        // the tokenizer resumes right after the first form
+        assert_eq!(
+            parser.next_expression_with_consumed().unwrap(),
+            (Some(Box::new(AST::NumberExpr(2.0))), 1)
+        );
+        assert_eq!(parser.next_expression_with_consumed().unwrap(), (None, 0));
+
+        // whitespace and comments count too, since they came off the tokenizer as well
+        let tok = GreedyTokenizer::new("  1 (f  2) ; done".as_bytes())
+            .unwrap()
+            .with_full_fidelity(true);
+
+        let mut parser = RecursiveDescentParser::new(Box::new(tok));
+        assert_eq!(
+            parser.next_expression_with_consumed().unwrap(),
+            (Some(Box::new(AST::NumberExpr(1.0))), 2)
+        );
+        let (_, consumed) = parser.next_expression_with_consumed().unwrap();
+        assert_eq!(consumed, 6);
+        assert_eq!(parser.next_expression_with_consumed().unwrap(), (None, 2));
     }
 }
@@ -0,0 +1,25 @@
+//! Helpers shared between the test modules.
+
+use crate::eval::{Evaluator, Value};
+use crate::parser::RecursiveDescentParser;
+
+/// Parses and evaluates every form in `source` with a fresh evaluator, then checks the last one
+/// gave back `expected`. Failures show both values as source, next to the source that made them.
+#[track_caller]
+pub fn assert_evals_to(source: &str, expected: Value) {
+    let mut parser = RecursiveDescentParser::from_source(source);
+    let mut evaluator = Evaluator::new();
+
+    let mut value = Value::Nil;
+    for ast in parser.parse_all().unwrap() {
+        value = evaluator.eval(&ast).unwrap();
+    }
+
+    assert!(
+        value == expected,
+        "`{}` evaluated to `{}`, not `{}`",
+        source,
+        value,
+        expected
+    );
+}
@@ -1,34 +1,110 @@
 use std::fmt::Display;
-use std::io::{self, Read};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 
 const SPACE_CHAR: char = ' ';
 const NEWLINE_CHAR: char = '\n';
 const CARRIAGE_RETURN_CHAR: char = '\r';
+const TAB_CHAR: char = '\t';
+const DEFAULT_COMMENT_CHAR: char = ';';
+const NUMBER_EPSILON: f64 = 1e-6;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     // standard symbols
     OpenParen,
     CloseParen,
+    OpenBracket,
+    CloseBracket,
+    OpenBrace,
+    CloseBrace,
+    // marks the rest parameter of a function
+    Ampersand,
+    // the `'` in front of a quoted form
+    Quote,
+
+    // threading macros
+    ThreadFirst,
+    ThreadLast,
+    SomeThreadFirst,
+    SomeThreadLast,
 
     // reserved keywords
+    Cond,
     Def,
+    Defn,
+    Do,
     Fn,
-    // If, // TODO
+    If,
+    Let,
+    Ns,
+    // only a special form at the head of a paren form, like `(quote x)`
+    QuoteForm,
+
+    // literals
+    Bool(bool),
+    Nil,
 
     // more complex stuff
     Identifier(String),
+    Keyword(String),
     Number(f64),
-    // StringLiteral(""), // TODO
+    String(String),
     Unknown(char),
+
+    // only emitted when the tokenizer keeps full fidelity
+    Whitespace(String),
+    Comment(String),
 }
 
 impl Token {
+    /// Compares two tokens, allowing numbers to differ by a small fraction of their size.
+    /// Everything else has to match exactly. Handy in tests where exact float equality would be
+    /// brittle.
+    pub fn approx_eq(&self, other: &Token) -> bool {
+        match (self, other) {
+            // infinities only ever match exactly, since any tolerance of them is infinite too
+            (Token::Number(lhs), Token::Number(rhs)) if lhs == rhs => true,
+            (Token::Number(lhs), Token::Number(rhs)) => {
+                lhs.is_finite()
+                    && rhs.is_finite()
+                    && (lhs - rhs).abs() <= NUMBER_EPSILON * lhs.abs().max(rhs.abs())
+            }
+            _ => self == other,
+        }
+    }
+
     fn from_str(string_value: &str) -> Option<Token> {
         match string_value {
+            "cond" => Some(Token::Cond),
             "def" => Some(Token::Def),
             "fn" => Some(Token::Fn),
-            // "if" => Some(Token::If),
+            "defn" => Some(Token::Defn),
+            "true" => Some(Token::Bool(true)),
+            "false" => Some(Token::Bool(false)),
+            "nil" => Some(Token::Nil),
+            "if" => Some(Token::If),
+            "let" => Some(Token::Let),
+            "do" => Some(Token::Do),
+            "ns" => Some(Token::Ns),
+            "quote" => Some(Token::QuoteForm),
+            "some->" => Some(Token::SomeThreadFirst),
+            "some->>" => Some(Token::SomeThreadLast),
+            _ => None,
+        }
+    }
+
+    /// The source text of reserved keyword tokens, which can't be used as names.
+    pub fn keyword(&self) -> Option<&'static str> {
+        match self {
+            Token::Cond => Some("cond"),
+            Token::Def => Some("def"),
+            Token::Fn => Some("fn"),
+            Token::Defn => Some("defn"),
+            Token::If => Some("if"),
+            Token::Let => Some("let"),
+            Token::Do => Some("do"),
+            Token::Ns => Some("ns"),
+            Token::QuoteForm => Some("quote"),
             _ => None,
         }
     }
@@ -39,17 +115,42 @@ impl Token {
             '-' => Some(Token::Identifier(String::from("-"))),
             '*' => Some(Token::Identifier(String::from("*"))),
             '/' => Some(Token::Identifier(String::from("/"))),
+            '=' => Some(Token::Identifier(String::from("="))),
+            '<' => Some(Token::Identifier(String::from("<"))),
+            '>' => Some(Token::Identifier(String::from(">"))),
+            '&' => Some(Token::Ampersand),
+            '\'' => Some(Token::Quote),
             _ => None,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Position {
     pub line: usize,
+    /// Counted in chars from the start of the line
+    pub column: usize,
+    /// Counted in bytes from the start of the input
     pub position: usize,
 }
 
+/// The stretch of source between two positions, both inclusive.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Span {
+    pub from: Position,
+    pub to: Position,
+}
+
+impl Span {
+    /// The smallest span covering both this one and `other`, along with anything between them.
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            from: self.from.clone().min(other.from.clone()),
+            to: self.to.clone().max(other.to.clone()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct TokenAndSpan {
     pub token: Token,
@@ -57,6 +158,15 @@ pub struct TokenAndSpan {
     pub to: Position,
 }
 
+impl TokenAndSpan {
+    pub fn span(&self) -> Span {
+        Span {
+            from: self.from.clone(),
+            to: self.to.clone(),
+        }
+    }
+}
+
 impl Display for TokenAndSpan {
     fn fmt(
         &self,
@@ -66,13 +176,13 @@ impl Display for TokenAndSpan {
             write!(
                 formatter,
                 "{:?}[line {} char {}]",
-                self.token, self.from.line, self.from.position
+                self.token, self.from.line, self.from.column
             )
         } else {
             write!(
                 formatter,
                 "{:?}[line {} char {} -> line {} char {}]",
-                self.token, self.from.line, self.from.position, self.to.line, self.to.position
+                self.token, self.from.line, self.from.column, self.to.line, self.to.column
             )
         }
     }
@@ -82,9 +192,20 @@ impl Display for TokenAndSpan {
 struct CharAndPosition {
     chr: Option<char>,
     line: usize,
+    column: usize,
     position: usize,
 }
 
+impl CharAndPosition {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+            position: self.position,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TokenizerError {
     IoError(io::Error),
@@ -116,6 +237,22 @@ impl TokenizerError {
     }
 }
 
+impl Clone for TokenizerError {
+    fn clone(&self) -> Self {
+        match self {
+            // io errors can't be cloned, so rebuild one carrying the same kind and message
+            TokenizerError::IoError(io_error) => {
+                TokenizerError::IoError(io::Error::new(io_error.kind(), io_error.to_string()))
+            }
+            TokenizerError::ReadError { message, from, to } => TokenizerError::ReadError {
+                message: message.clone(),
+                from: from.clone(),
+                to: to.clone(),
+            },
+        }
+    }
+}
+
 // hack: just get it working for tests
 impl PartialEq for TokenizerError {
     fn eq(&self, rhs: &TokenizerError) -> bool {
@@ -127,85 +264,264 @@ pub trait Tokenizer: Iterator<Item = Result<TokenAndSpan, TokenizerError>> {}
 
 impl<T: Iterator<Item = Result<TokenAndSpan, TokenizerError>>> Tokenizer for T {}
 
+pub trait TokenizerExt: Tokenizer + Sized {
+    /// Only keep the tokens for which `predicate` returns true. Errors are always passed along.
+    fn filter_tokens<P>(self, predicate: P) -> FilterTokens<Self, P>
+    where
+        P: FnMut(&Token) -> bool,
+    {
+        FilterTokens {
+            tokenizer: self,
+            predicate,
+        }
+    }
+}
+
+impl<T: Tokenizer> TokenizerExt for T {}
+
+pub struct FilterTokens<T, P> {
+    tokenizer: T,
+    predicate: P,
+}
+
+impl<T, P> Iterator for FilterTokens<T, P>
+where
+    T: Tokenizer,
+    P: FnMut(&Token) -> bool,
+{
+    type Item = Result<TokenAndSpan, TokenizerError>;
+
+    fn next(&mut self) -> Option<Result<TokenAndSpan, TokenizerError>> {
+        loop {
+            match self.tokenizer.next()? {
+                Ok(token_and_span) if !(self.predicate)(&token_and_span.token) => continue,
+                item => return Some(item),
+            }
+        }
+    }
+}
+
 pub struct GreedyTokenizer<T>
 where
     T: Read,
 {
-    inbuf: T,
+    // chars get read a byte at a time, so reads from the source are batched up behind a buffer
+    inbuf: BufReader<T>,
     line: usize,
+    column: usize,
     position: usize,
     current_char: CharAndPosition,
+    // set while the current char stands in for invalid bytes, until the error gets reported
+    invalid_sequence: Option<TokenizerError>,
+    strict_leading_zeros: bool,
+    strict_control_chars: bool,
+    full_fidelity: bool,
+    comment_char: char,
+    last_emitted: Option<Position>,
+    peeked: Option<Option<Result<TokenAndSpan, TokenizerError>>>,
 }
 
 impl<T> GreedyTokenizer<T>
 where
     T: Read,
 {
-    pub fn new(inbuf: T) -> io::Result<Self> {
+    pub fn new(inbuf: T) -> Result<Self, TokenizerError> {
         let mut tok = GreedyTokenizer {
-            inbuf,
+            inbuf: BufReader::new(inbuf),
             line: 1,
+            column: 0,
             position: 0,
             current_char: CharAndPosition {
                 chr: None,
                 line: 1,
+                column: 0,
                 position: 0,
             },
+            invalid_sequence: None,
+            strict_leading_zeros: false,
+            strict_control_chars: false,
+            full_fidelity: false,
+            comment_char: DEFAULT_COMMENT_CHAR,
+            last_emitted: None,
+            peeked: None,
         };
 
         // start it off
         tok.step_next_char()?;
+        if let Some(read_error) = tok.invalid_sequence.take() {
+            return Err(read_error);
+        }
 
         Ok(tok)
     }
 
-    fn step_next_char(&mut self) -> io::Result<()> {
-        let mut buffer: [u8; 1] = [0];
-        let chars_read = self.inbuf.read(&mut buffer)?;
+    /// Reject number literals with superfluous leading zeros like `007`, which some dialects read
+    /// as octal. Off by default.
+    pub fn with_strict_leading_zeros(mut self, strict: bool) -> Self {
+        self.strict_leading_zeros = strict;
+        self
+    }
+
+    /// Reject raw control characters (other than tab, newline and carriage return) instead of
+    /// emitting them as `Token::Unknown`. Off by default.
+    pub fn with_strict_control_chars(mut self, strict: bool) -> Self {
+        self.strict_control_chars = strict;
+        self
+    }
+
+    /// Emit whitespace and comments as `Token::Whitespace` and `Token::Comment` instead of
+    /// skipping them, so the source can be rebuilt exactly from the tokens' spans. Off by default.
+    pub fn with_full_fidelity(mut self, full_fidelity: bool) -> Self {
+        self.full_fidelity = full_fidelity;
+        self
+    }
+
+    /// Start line comments with `comment_char` instead of `;`, like `#` for shell-style comments.
+    /// Special reads like `##Inf` still read as numbers either way.
+    pub fn with_comment_char(mut self, comment_char: char) -> Self {
+        self.comment_char = comment_char;
+        self
+    }
+
+    /// Look at the next token without consuming it. The following call to `next` hands back the
+    /// same token.
+    pub fn peek(&mut self) -> Option<&Result<TokenAndSpan, TokenizerError>> {
+        if self.peeked.is_none() {
+            let next = self.next();
+            self.peeked = Some(next);
+        }
+
+        self.peeked.as_ref().and_then(Option::as_ref)
+    }
 
-        if chars_read > 0 {
-            let chr = buffer[0] as char;
+    fn step_next_char(&mut self) -> Result<(), TokenizerError> {
+        // stepping past invalid bytes that weren't reported as a token of their own reports them
+        let stepped_past = self.invalid_sequence.take();
+
+        let (line, column, position) = (self.line, self.column, self.position);
+        let next_char = match self.read_char() {
+            Ok(next_char) => next_char,
+            Err(io_error @ TokenizerError::IoError(_)) => return Err(io_error),
+            // invalid bytes become a replacement char, so they end whatever token came before them
+            Err(read_error) => {
+                self.invalid_sequence = Some(read_error);
+                Some(char::REPLACEMENT_CHARACTER)
+            }
+        };
 
-            self.current_char = CharAndPosition {
-                chr: Some(chr),
-                line: self.line,
-                position: self.position,
-            };
+        self.current_char = CharAndPosition {
+            chr: next_char,
+            line,
+            column,
+            position,
+        };
 
-            self.position += 1;
+        // read_char already moved past any invalid bytes
+        if let (None, Some(chr)) = (&self.invalid_sequence, next_char) {
+            self.position += chr.len_utf8();
+            self.column += 1;
             if chr == '\n' || chr == '\r' {
                 self.line += 1;
-                self.position = 0;
+                self.column = 0;
             }
-        } else {
-            self.current_char = CharAndPosition {
-                chr: None,
-                line: self.line,
-                position: self.position,
-            };
         }
 
-        Ok(())
+        match stepped_past {
+            Some(read_error) => Err(read_error),
+            None => Ok(()),
+        }
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, TokenizerError> {
+        Ok(self.inbuf.fill_buf()?.first().copied())
+    }
+
+    fn read_char(&mut self) -> Result<Option<char>, TokenizerError> {
+        let mut bytes = match self.peek_byte()? {
+            Some(byte) => vec![byte],
+            None => return Ok(None),
+        };
+        self.inbuf.consume(1);
+
+        // the leading byte says how many continuation bytes make up the rest of the char, but
+        // only continuation bytes get taken so a bad sequence never swallows the char after it
+        let width = match bytes[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        while bytes.len() < width {
+            match self.peek_byte()? {
+                Some(byte @ 0x80..=0xbf) => {
+                    bytes.push(byte);
+                    self.inbuf.consume(1);
+                }
+                _ => break,
+            }
+        }
+
+        if let Ok(decoded) = std::str::from_utf8(&bytes) {
+            return Ok(decoded.chars().next());
+        }
+
+        // bytes that can't start a char either are part of the same bad run
+        while let Some(byte @ (0x80..=0xbf | 0xc0 | 0xc1 | 0xf5..=0xff)) = self.peek_byte()? {
+            bytes.push(byte);
+            self.inbuf.consume(1);
+        }
+
+        // the invalid bytes still take up room in the input, a column each
+        let from = Position {
+            line: self.line,
+            column: self.column,
+            position: self.position,
+        };
+        self.position += bytes.len();
+        self.column += bytes.len();
+        let to = Position {
+            line: self.line,
+            column: self.column - 1,
+            position: self.position - 1,
+        };
+
+        Err(TokenizerError::ReadError {
+            message: format!("Invalid UTF-8 sequence {:x?}", bytes),
+            from,
+            to,
+        })
     }
 
-    fn fast_forward_comments_and_spaces(&mut self) -> Result<(), TokenizerError> {
+    /// Skips whitespace and comments. If what looked like a comment turns out to be a special
+    /// read like `##Inf`, stops right after the `##` and returns where the first `#` was.
+    fn fast_forward_comments_and_spaces(
+        &mut self,
+    ) -> Result<Option<CharAndPosition>, TokenizerError> {
         let start_tok = self.current_char;
         let mut tok = self.current_char;
 
         // remove any whitespace
-        while tok.chr == Some(SPACE_CHAR)
-            || tok.chr == Some(NEWLINE_CHAR)
-            || tok.chr == Some(CARRIAGE_RETURN_CHAR)
-        {
+        while is_whitespace(&tok) {
             self.step_next_char()?;
             tok = self.current_char;
         }
 
         // ignore comments - this could go to the end of the line
-        if tok.chr == Some('#') {
+        if tok.chr == Some(self.comment_char) {
+            let hash = tok;
+            self.step_next_char()?;
+            if self.comment_char == '#' && self.current_char.chr == Some('#') {
+                self.step_next_char()?;
+                if is_special_read_start(&self.current_char) {
+                    return Ok(Some(hash));
+                }
+            }
+
+            tok = self.current_char;
             while tok.chr != Some(NEWLINE_CHAR)
                 && tok.chr != Some(CARRIAGE_RETURN_CHAR)
-                && tok.chr != None
+                && tok.chr.is_some()
             {
                 self.step_next_char()?;
                 tok = self.current_char;
@@ -214,64 +530,400 @@ where
 
         // if we ended up in a new line, we need to process more spaces
         if self.current_char != start_tok {
-            self.fast_forward_comments_and_spaces()?;
+            return self.fast_forward_comments_and_spaces();
         }
 
-        Ok(())
+        Ok(None)
+    }
+
+    fn read_whitespace_or_comment(&mut self) -> Result<Option<TokenAndSpan>, TokenizerError> {
+        let mut tok = self.current_char;
+        let from = tok.position();
+        let mut text = String::new();
+        let mut last_consumed = tok;
+
+        let token = if is_whitespace(&tok) {
+            while is_whitespace(&tok) {
+                text.push(tok.chr.unwrap());
+                last_consumed = tok;
+                self.step_next_char()?;
+                tok = self.current_char;
+            }
+            Token::Whitespace(text)
+        } else if tok.chr == Some(self.comment_char) {
+            let hash = tok;
+            for _ in 0..2 {
+                if tok.chr != Some(self.comment_char) {
+                    break;
+                }
+                text.push(self.comment_char);
+                last_consumed = tok;
+                self.step_next_char()?;
+                tok = self.current_char;
+            }
+            if text == "##" && is_special_read_start(&tok) {
+                return self.read_special_number(hash).map(Some);
+            }
+
+            // the comment runs up to, but not including, the end of the line
+            while tok.chr != Some(NEWLINE_CHAR)
+                && tok.chr != Some(CARRIAGE_RETURN_CHAR)
+                && tok.chr.is_some()
+            {
+                text.push(tok.chr.unwrap());
+                last_consumed = tok;
+                self.step_next_char()?;
+                tok = self.current_char;
+            }
+            Token::Comment(text)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(TokenAndSpan {
+            token,
+            from,
+            to: last_consumed.position(),
+        }))
+    }
+
+    fn read_string_literal(&mut self) -> Result<TokenAndSpan, TokenizerError> {
+        let from = self.current_char.position();
+        let mut text = String::new();
+        let mut escape_error = None;
+
+        let mut last_consumed = self.current_char;
+        self.step_next_char()?;
+        let mut tok = self.current_char;
+
+        // read up to the closing quote, decoding escapes along the way
+        while tok.chr != Some('"') {
+            match tok.chr {
+                Some('\\') => {
+                    let escape_start = tok;
+                    last_consumed = tok;
+                    self.step_next_char()?;
+                    tok = self.current_char;
+
+                    match tok.chr {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some('"') => text.push('"'),
+                        Some('\\') => text.push('\\'),
+                        // \u and four hex digits name a unicode scalar, like \u00e9 for é
+                        Some('u') => {
+                            let mut hex = String::new();
+                            while hex.len() < 4 {
+                                last_consumed = tok;
+                                self.step_next_char()?;
+                                tok = self.current_char;
+                                match tok.chr {
+                                    Some(digit) if digit.is_ascii_hexdigit() => hex.push(digit),
+                                    _ => break,
+                                }
+                            }
+
+                            if hex.len() < 4 {
+                                escape_error.get_or_insert(TokenizerError::ReadError {
+                                    message: String::from("Unicode escape needs four hex digits"),
+                                    from: escape_start.position(),
+                                    to: last_consumed.position(),
+                                });
+                                // the char that cut the escape short still needs reading
+                                continue;
+                            }
+                            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                Some(chr) => text.push(chr),
+                                None => {
+                                    escape_error.get_or_insert(TokenizerError::ReadError {
+                                        message: format!(
+                                            "Unicode escape '\\u{}' isn't a valid code point",
+                                            hex
+                                        ),
+                                        from: escape_start.position(),
+                                        to: tok.position(),
+                                    });
+                                }
+                            }
+                        }
+                        // keep going to the closing quote so we resume after the string
+                        Some(chr) if escape_error.is_none() => {
+                            escape_error = Some(TokenizerError::ReadError {
+                                message: format!("Unknown escape sequence '\\{}'", chr),
+                                from: escape_start.position(),
+                                to: tok.position(),
+                            })
+                        }
+                        Some(_) => {}
+                        None => continue,
+                    }
+                }
+                Some(chr) => text.push(chr),
+                None => {
+                    return Err(TokenizerError::ReadError {
+                        message: String::from("Unterminated string literal"),
+                        from,
+                        to: last_consumed.position(),
+                    })
+                }
+            }
+
+            last_consumed = tok;
+            self.step_next_char()?;
+            tok = self.current_char;
+        }
+
+        // step over the closing quote
+        self.step_next_char()?;
+
+        match escape_error {
+            Some(err) => Err(err),
+            None => Ok(TokenAndSpan {
+                token: Token::String(text),
+                from,
+                to: tok.position(),
+            }),
+        }
+    }
+
+    /// Reads the name of a special read like `##Inf`, with the tokenizer just past the `##`.
+    fn read_special_number(
+        &mut self,
+        hash: CharAndPosition,
+    ) -> Result<TokenAndSpan, TokenizerError> {
+        let mut name = String::new();
+        let mut tok = self.current_char;
+        let mut last_consumed = tok;
+
+        while is_identifier_like(&tok) || tok.chr == Some('-') {
+            name.push(tok.chr.unwrap());
+            last_consumed = tok;
+            self.step_next_char()?;
+            tok = self.current_char;
+        }
+
+        let from = hash.position();
+        let to = last_consumed.position();
+        let value = match name.as_str() {
+            "Inf" => f64::INFINITY,
+            "-Inf" => f64::NEG_INFINITY,
+            "NaN" => f64::NAN,
+            _ => {
+                return Err(TokenizerError::ReadError {
+                    message: format!("Unknown special read '##{}'", name),
+                    from,
+                    to,
+                })
+            }
+        };
+
+        Ok(TokenAndSpan {
+            token: Token::Number(value),
+            from,
+            to,
+        })
+    }
+
+    /// Reads a number starting at the current char. `minus` is the `-` in front of it, if a
+    /// negative number's sign was already consumed.
+    fn read_number(
+        &mut self,
+        minus: Option<CharAndPosition>,
+    ) -> Result<TokenAndSpan, TokenizerError> {
+        let mut tok = self.current_char;
+        let mut numstr = String::new();
+        let from = match minus {
+            Some(minus) => {
+                numstr.push('-');
+                minus.position()
+            }
+            None => tok.position(),
+        };
+
+        let mut last_consumed = tok;
+        while is_number_like(&tok) || is_exponent_marker(&tok) || tok.chr == Some('_') {
+            let chr = tok.chr.unwrap();
+            numstr.push(chr);
+            last_consumed = tok;
+            self.step_next_char()?;
+            tok = self.current_char;
+
+            // exponents can have a sign of their own
+            if (chr == 'e' || chr == 'E') && (tok.chr == Some('+') || tok.chr == Some('-')) {
+                numstr.push(tok.chr.unwrap());
+                last_consumed = tok;
+                self.step_next_char()?;
+                tok = self.current_char;
+            }
+        }
+        let to = last_consumed.position();
+
+        // underscores are only there to group digits for readability
+        let digits = numstr.replace('_', "");
+        if self.strict_leading_zeros && has_leading_zero(&digits) {
+            return Err(TokenizerError::ReadError {
+                message: format!("Number '{}' has a superfluous leading zero", numstr),
+                from,
+                to,
+            });
+        }
+
+        match digits.parse() {
+            Ok(parsed) => Ok(TokenAndSpan {
+                token: Token::Number(parsed),
+                from,
+                to,
+            }),
+            Err(e) => Err(TokenizerError::from(numstr, from, to, e)),
+        }
+    }
+
+    /// Reads a keyword starting at its `:`. The name it keeps doesn't include the colon.
+    fn read_keyword(&mut self) -> Result<TokenAndSpan, TokenizerError> {
+        let colon = self.current_char;
+        self.step_next_char()?;
+
+        let mut name = String::new();
+        let mut last_consumed = colon;
+        while is_identifier_like(&self.current_char) || self.current_char.chr == Some('/') {
+            name.push(self.current_char.chr.unwrap());
+            last_consumed = self.current_char;
+            self.step_next_char()?;
+        }
+
+        if name.is_empty() {
+            return Err(TokenizerError::ReadError {
+                message: String::from("Keyword is missing a name after ':'"),
+                from: colon.position(),
+                to: colon.position(),
+            });
+        }
+
+        // a namespace needs a name on both sides of its slash, like :db/id
+        if name.starts_with('/') || name.ends_with('/') {
+            return Err(TokenizerError::ReadError {
+                message: format!("Keyword ':{}' can't start or end with '/'", name),
+                from: colon.position(),
+                to: last_consumed.position(),
+            });
+        }
+
+        Ok(TokenAndSpan {
+            token: Token::Keyword(name),
+            from: colon.position(),
+            to: last_consumed.position(),
+        })
+    }
+
+    /// Reads the rest of a `->` or `->>` arrow, given the `-` it started with was consumed.
+    fn read_threading_arrow(
+        &mut self,
+        dash: CharAndPosition,
+    ) -> Result<TokenAndSpan, TokenizerError> {
+        let mut last_consumed = self.current_char;
+        self.step_next_char()?;
+
+        let token = if self.current_char.chr == Some('>') {
+            last_consumed = self.current_char;
+            self.step_next_char()?;
+            Token::ThreadLast
+        } else {
+            Token::ThreadFirst
+        };
+
+        Ok(TokenAndSpan {
+            token,
+            from: dash.position(),
+            to: last_consumed.position(),
+        })
     }
 
     fn move_to_next_token(&mut self) -> Result<Option<TokenAndSpan>, TokenizerError> {
-        self.fast_forward_comments_and_spaces()?;
+        if self.full_fidelity {
+            if let Some(token_and_span) = self.read_whitespace_or_comment()? {
+                return Ok(Some(token_and_span));
+            }
+        } else if let Some(hash) = self.fast_forward_comments_and_spaces()? {
+            return self.read_special_number(hash).map(Some);
+        }
+
+        // invalid bytes are reported where a token would have started
+        if let Some(read_error) = self.invalid_sequence.take() {
+            self.step_next_char()?;
+            return Err(read_error);
+        }
 
         let mut tok = self.current_char;
 
-        // find parens
+        // find parens, brackets and braces
         if tok.chr == Some('(') {
             self.step_next_char()?;
             return Ok(Some(TokenAndSpan {
                 token: Token::OpenParen,
-                from: Position {
-                    line: tok.line,
-                    position: tok.position,
-                },
-                to: Position {
-                    line: tok.line,
-                    position: tok.position,
-                },
+                from: tok.position(),
+                to: tok.position(),
             }));
         } else if tok.chr == Some(')') {
             self.step_next_char()?;
             return Ok(Some(TokenAndSpan {
                 token: Token::CloseParen,
-                from: Position {
-                    line: tok.line,
-                    position: tok.position,
-                },
-                to: Position {
-                    line: tok.line,
-                    position: tok.position,
-                },
+                from: tok.position(),
+                to: tok.position(),
             }));
+        } else if tok.chr == Some('[') {
+            self.step_next_char()?;
+            return Ok(Some(TokenAndSpan {
+                token: Token::OpenBracket,
+                from: tok.position(),
+                to: tok.position(),
+            }));
+        } else if tok.chr == Some(']') {
+            self.step_next_char()?;
+            return Ok(Some(TokenAndSpan {
+                token: Token::CloseBracket,
+                from: tok.position(),
+                to: tok.position(),
+            }));
+        } else if tok.chr == Some('{') {
+            self.step_next_char()?;
+            return Ok(Some(TokenAndSpan {
+                token: Token::OpenBrace,
+                from: tok.position(),
+                to: tok.position(),
+            }));
+        } else if tok.chr == Some('}') {
+            self.step_next_char()?;
+            return Ok(Some(TokenAndSpan {
+                token: Token::CloseBrace,
+                from: tok.position(),
+                to: tok.position(),
+            }));
+        }
+
+        // recognize string literals
+        if tok.chr == Some('"') {
+            return self.read_string_literal().map(Some);
+        }
+
+        // keywords like :name or :db/id
+        if tok.chr == Some(':') {
+            return self.read_keyword().map(Some);
         }
 
         // recognize any identifiers
         if is_alphabetic(&tok) {
             let mut ident = String::new();
-            let from = Position {
-                line: tok.line,
-                position: tok.position,
-            };
+            let from = tok.position();
 
+            let mut last_consumed = tok;
             while is_identifier_like(&tok) {
                 ident.push(tok.chr.unwrap());
+                last_consumed = tok;
                 self.step_next_char()?;
                 tok = self.current_char;
             }
 
-            let to = Position {
-                line: tok.line,
-                position: tok.position - 1,
-            };
+            let to = last_consumed.position();
             if let Some(reserved_token) = Token::from_str(&ident) {
                 return Ok(Some(TokenAndSpan {
                     token: reserved_token,
@@ -289,59 +941,54 @@ where
 
         // recognizing any numeric things
         if is_number_like(&tok) {
-            let mut numstr = String::new();
-            let from = Position {
-                line: tok.line,
-                position: tok.position,
-            };
-
-            while is_number_like(&tok) {
-                numstr.push(tok.chr.unwrap());
-                self.step_next_char()?;
-                tok = self.current_char;
-            }
-            let to = Position {
-                line: tok.line,
-                position: tok.position - 1,
-            };
-
-            match numstr.parse() {
-                Ok(parsed) => {
-                    return Ok(Some(TokenAndSpan {
-                        token: Token::Number(parsed),
-                        from,
-                        to,
-                    }))
-                }
-                Err(e) => return Err(TokenizerError::from(numstr, from, to, e)),
-            }
+            return self.read_number(None).map(Some);
         }
 
         // every other case is either a reserved char, EOF or simply an unknown char
         self.step_next_char()?;
+
+        // unless it's the start of a -> or ->> arrow, or a negative number
+        if tok.chr == Some('-') && self.current_char.chr == Some('>') {
+            return self.read_threading_arrow(tok).map(Some);
+        } else if tok.chr == Some('-') && is_digit(&self.current_char) {
+            return self.read_number(Some(tok)).map(Some);
+        }
+
+        // or a special read like ##Inf, when # doesn't start comments
+        if tok.chr == Some('#') && self.current_char.chr == Some('#') {
+            self.step_next_char()?;
+            return self.read_special_number(tok).map(Some);
+        }
+
+        // or a <= or >= comparison
+        if (tok.chr == Some('<') || tok.chr == Some('>')) && self.current_char.chr == Some('=') {
+            let equals = self.current_char;
+            self.step_next_char()?;
+            return Ok(Some(TokenAndSpan {
+                token: Token::Identifier(format!("{}=", tok.chr.unwrap())),
+                from: tok.position(),
+                to: equals.position(),
+            }));
+        }
+
         match tok.chr {
             Some(char_value) => match Token::from_char(char_value) {
                 Some(token) => Ok(Some(TokenAndSpan {
                     token,
-                    from: Position {
-                        line: tok.line,
-                        position: tok.position,
-                    },
-                    to: Position {
-                        line: tok.line,
-                        position: tok.position,
-                    },
+                    from: tok.position(),
+                    to: tok.position(),
                 })),
+                None if self.strict_control_chars && is_disallowed_control(char_value) => {
+                    Err(TokenizerError::ReadError {
+                        message: format!("Control character {:?} is not allowed", char_value),
+                        from: tok.position(),
+                        to: tok.position(),
+                    })
+                }
                 None => Ok(Some(TokenAndSpan {
                     token: Token::Unknown(tok.chr.unwrap()),
-                    from: Position {
-                        line: tok.line,
-                        position: tok.position,
-                    },
-                    to: Position {
-                        line: tok.line,
-                        position: tok.position,
-                    },
+                    from: tok.position(),
+                    to: tok.position(),
                 })),
             },
             None => Ok(None),
@@ -349,21 +996,130 @@ where
     }
 }
 
-impl<T> Iterator for GreedyTokenizer<T>
+impl<T> GreedyTokenizer<T>
 where
-    T: Read,
+    T: Read + Seek,
+{
+    /// Rewind to the start of the source, so it can be tokenized again from the top. Options set
+    /// with the builders are kept.
+    pub fn reset(&mut self) -> Result<(), TokenizerError> {
+        self.inbuf.seek(SeekFrom::Start(0))?;
+        self.line = 1;
+        self.column = 0;
+        self.position = 0;
+        self.current_char = CharAndPosition {
+            chr: None,
+            line: 1,
+            column: 0,
+            position: 0,
+        };
+        self.invalid_sequence = None;
+        self.last_emitted = None;
+        self.peeked = None;
+
+        self.step_next_char()
+    }
+}
+
+impl<T> Iterator for GreedyTokenizer<T>
+where
+    T: Read,
 {
     type Item = Result<TokenAndSpan, TokenizerError>;
 
     fn next(&mut self) -> Option<Result<TokenAndSpan, TokenizerError>> {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked;
+        }
+
         match self.move_to_next_token() {
-            Ok(Some(item)) => Some(Ok(item)),
+            Ok(Some(item)) => {
+                // spans must be well formed and strictly follow one another
+                debug_assert!(item.from <= item.to, "span ends before it starts: {}", item);
+                debug_assert!(
+                    self.last_emitted
+                        .as_ref()
+                        .is_none_or(|last| *last < item.from),
+                    "span overlaps the previous token: {}",
+                    item
+                );
+                self.last_emitted = Some(item.to.clone());
+
+                Some(Ok(item))
+            }
             Ok(None) => None,
             Err(item) => Some(Err(item)),
         }
     }
 }
 
+/// A point in a `BacktrackingTokenizer`'s stream that it can be reset back to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Mark(usize);
+
+/// Wraps a tokenizer and remembers every token it hands out, so that a parser trying out one
+/// interpretation of the input can `reset` to a `mark` and try another. Meant for in-memory
+/// sources, since nothing is ever dropped from the buffer.
+pub struct BacktrackingTokenizer<T>
+where
+    T: Tokenizer,
+{
+    tokenizer: T,
+    buffer: Vec<Result<TokenAndSpan, TokenizerError>>,
+    index: usize,
+}
+
+impl<T> BacktrackingTokenizer<T>
+where
+    T: Tokenizer,
+{
+    pub fn new(tokenizer: T) -> Self {
+        Self {
+            tokenizer,
+            buffer: vec![],
+            index: 0,
+        }
+    }
+
+    pub fn mark(&self) -> Mark {
+        Mark(self.index)
+    }
+
+    pub fn reset(&mut self, mark: Mark) {
+        self.index = mark.0;
+    }
+}
+
+impl<T> Iterator for BacktrackingTokenizer<T>
+where
+    T: Tokenizer,
+{
+    type Item = Result<TokenAndSpan, TokenizerError>;
+
+    fn next(&mut self) -> Option<Result<TokenAndSpan, TokenizerError>> {
+        if self.index == self.buffer.len() {
+            self.buffer.push(self.tokenizer.next()?);
+        }
+
+        self.index += 1;
+        Some(self.buffer[self.index - 1].clone())
+    }
+}
+
+fn is_whitespace(tok: &CharAndPosition) -> bool {
+    tok.chr == Some(SPACE_CHAR)
+        || tok.chr == Some(NEWLINE_CHAR)
+        || tok.chr == Some(CARRIAGE_RETURN_CHAR)
+}
+
+fn is_disallowed_control(chr: char) -> bool {
+    chr < SPACE_CHAR && ![TAB_CHAR, NEWLINE_CHAR, CARRIAGE_RETURN_CHAR].contains(&chr)
+}
+
+fn is_special_read_start(tok: &CharAndPosition) -> bool {
+    is_alphabetic(tok) || tok.chr == Some('-')
+}
+
 fn is_alphabetic(tok: &CharAndPosition) -> bool {
     if let Some(chr) = tok.chr {
         chr.is_alphabetic()
@@ -374,7 +1130,7 @@ fn is_alphabetic(tok: &CharAndPosition) -> bool {
 
 fn is_identifier_like(tok: &CharAndPosition) -> bool {
     if let Some(chr) = tok.chr {
-        chr.is_alphanumeric() || chr == '_'
+        chr.is_alphanumeric() || ['_', '?', '!', '-', '*', '>', '=', '.', '/'].contains(&chr)
     } else {
         false
     }
@@ -388,10 +1144,76 @@ fn is_number_like(tok: &CharAndPosition) -> bool {
     }
 }
 
+fn is_exponent_marker(tok: &CharAndPosition) -> bool {
+    tok.chr == Some('e') || tok.chr == Some('E')
+}
+
+fn is_digit(tok: &CharAndPosition) -> bool {
+    tok.chr.is_some_and(|chr| chr.is_ascii_digit())
+}
+
+fn has_leading_zero(numstr: &str) -> bool {
+    let mut chars = numstr.trim_start_matches('-').chars();
+    chars.next() == Some('0') && chars.next().is_some_and(|chr| chr.is_numeric())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Hands out at most `chunk` bytes per read, and counts how many reads it took.
+    struct ChunkedReader<'a> {
+        source: &'a [u8],
+        chunk: usize,
+        reads: usize,
+    }
+
+    impl<'a> ChunkedReader<'a> {
+        fn new(source: &'a [u8], chunk: usize) -> Self {
+            ChunkedReader {
+                source,
+                chunk,
+                reads: 0,
+            }
+        }
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            let len = self.chunk.min(buf.len()).min(self.source.len());
+            buf[..len].copy_from_slice(&self.source[..len]);
+            self.source = &self.source[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn it_tokenizes_the_same_however_the_input_is_split() -> Result<(), TokenizerError> {
+        let source = "(def ü \"ŝtring\") ; cömment\n(f 1.5 ##Inf [x] {:k 1})".as_bytes();
+        let expected = GreedyTokenizer::new(source)?.collect::<Vec<_>>();
+
+        for chunk in 1..5 {
+            let mut reader = ChunkedReader::new(source, chunk);
+            let tokens = GreedyTokenizer::new(&mut reader)?.collect::<Vec<_>>();
+            assert_eq!(tokens, expected, "split into reads of {} bytes", chunk);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_batches_up_reads_from_the_input() -> Result<(), TokenizerError> {
+        let source = "(+ 1 2) ; three\n".repeat(500);
+        let mut reader = ChunkedReader::new(source.as_bytes(), usize::MAX);
+        assert_eq!(GreedyTokenizer::new(&mut reader)?.count(), 2500);
+
+        // rather than a read for every byte of the 8000 there are
+        assert!(reader.reads < 5, "took {} reads", reader.reads);
+
+        Ok(())
+    }
+
     #[test]
     fn it_handles_empty_buffer() -> Result<(), TokenizerError> {
         let inbuf = &b""[..];
@@ -405,21 +1227,57 @@ mod tests {
 
     #[test]
     fn it_ignores_file_containing_only_comments() -> Result<(), TokenizerError> {
-        let inbuf = &b"# blah"[..];
+        let inbuf = &b"; blah"[..];
         assert!(GreedyTokenizer::new(inbuf)?.next().is_none());
 
-        let inbuf = &b"  # blah"[..];
+        let inbuf = &b"  ; blah"[..];
         assert!(GreedyTokenizer::new(inbuf)?.next().is_none());
 
-        let mut handler = GreedyTokenizer::new(&b"  # only \n # comments"[..])?;
+        let mut handler = GreedyTokenizer::new(&b"  ; only \n ; comments"[..])?;
         assert!(handler.next().is_none());
 
-        let mut handler = GreedyTokenizer::new(&b"  # only \r # comments"[..])?;
+        let mut handler = GreedyTokenizer::new(&b"  ; only \r ; comments"[..])?;
         assert!(handler.next().is_none());
 
         Ok(())
     }
 
+    #[test]
+    fn it_skips_lines_starting_with_the_comment_char() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b";; the answer\n42 ; again\n;"[..])?
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(tokens, vec![Token::Number(42.0)]);
+
+        // a hash is only a comment when asked for, but special reads work either way
+        let tokens = GreedyTokenizer::new(&b"# x\n##Inf"[..])?
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Unknown('#'),
+                Token::Identifier(String::from("x")),
+                Token::Number(f64::INFINITY)
+            ]
+        );
+
+        let tokens = GreedyTokenizer::new(&b"# x\n##Inf ; y"[..])?
+            .with_comment_char('#')
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(f64::INFINITY),
+                Token::Unknown(';'),
+                Token::Identifier(String::from("y"))
+            ]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_handles_parens() -> Result<(), TokenizerError> {
         let mut handler = GreedyTokenizer::new(&b"("[..])?;
@@ -429,27 +1287,31 @@ mod tests {
                 token: Token::OpenParen,
                 from: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 },
                 to: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 }
             }
         );
         assert!(handler.next().is_none());
 
-        let mut handler = GreedyTokenizer::new(&b"   ()  # whodat"[..])?;
+        let mut handler = GreedyTokenizer::new(&b"   ()  ; whodat"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
                 token: Token::OpenParen,
                 from: Position {
                     line: 1,
+                    column: 3,
                     position: 3
                 },
                 to: Position {
                     line: 1,
+                    column: 3,
                     position: 3
                 }
             }
@@ -460,10 +1322,12 @@ mod tests {
                 token: Token::CloseParen,
                 from: Position {
                     line: 1,
+                    column: 4,
                     position: 4
                 },
                 to: Position {
                     line: 1,
+                    column: 4,
                     position: 4
                 }
             }
@@ -474,255 +1338,1628 @@ mod tests {
     }
 
     #[test]
-    fn it_handles_multiple_parens() -> Result<(), TokenizerError> {
-        let mut handler = GreedyTokenizer::new(&b"(())"[..])?;
-
-        // two open parens
-        for position in 0..2 {
-            assert_eq!(
-                handler.next().unwrap()?,
-                TokenAndSpan {
-                    token: Token::OpenParen,
-                    from: Position { line: 1, position },
-                    to: Position { line: 1, position }
-                }
-            );
-        }
-
-        // two close parens
-        for position in 2..4 {
-            assert_eq!(
-                handler.next().unwrap()?,
-                TokenAndSpan {
-                    token: Token::CloseParen,
-                    from: Position { line: 1, position },
-                    to: Position { line: 1, position }
-                }
-            );
-        }
-
-        // an eof at the end
-        assert!(handler.next().is_none());
-
-        Ok(())
-    }
-
-    #[test]
-    fn it_handles_identifier_token() -> Result<(), TokenizerError> {
-        let mut handler = GreedyTokenizer::new(&b"some_1dentifier"[..])?;
+    fn it_handles_threading_arrows() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"(-> x) ->>- -"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::Identifier(String::from("some_1dentifier")),
+                token: Token::ThreadFirst,
                 from: Position {
                     line: 1,
-                    position: 0
+                    column: 1,
+                    position: 1
                 },
                 to: Position {
                     line: 1,
-                    position: 14
+                    column: 2,
+                    position: 2
                 }
             }
         );
-        assert!(handler.next().is_none());
-
-        let mut handler = GreedyTokenizer::new(&b"   w1432)  # whodat"[..])?;
         assert_eq!(
-            handler.next().unwrap()?,
-            TokenAndSpan {
-                token: Token::Identifier(String::from("w1432")),
-                from: Position {
-                    line: 1,
-                    position: 3
-                },
-                to: Position {
-                    line: 1,
-                    position: 7
-                }
-            }
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("x"))
         );
+        assert_eq!(handler.next().unwrap()?.token, Token::CloseParen);
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::CloseParen,
+                token: Token::ThreadLast,
                 from: Position {
                     line: 1,
-                    position: 8
+                    column: 7,
+                    position: 7
                 },
                 to: Position {
                     line: 1,
-                    position: 8
+                    column: 9,
+                    position: 9
                 }
             }
         );
+
+        // a dash on its own is still subtraction
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("-"))
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("-"))
+        );
         assert!(handler.next().is_none());
 
         Ok(())
     }
 
     #[test]
-    fn it_handles_numeric_token() -> Result<(), TokenizerError> {
-        let mut handler = GreedyTokenizer::new(&b"120"[..])?;
+    fn it_handles_nil_safe_threading_arrows() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"some-> some->> some-x some"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::Number(120.0),
+                token: Token::SomeThreadFirst,
                 from: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 },
                 to: Position {
                     line: 1,
-                    position: 2
+                    column: 5,
+                    position: 5
                 }
             }
         );
-        assert!(handler.next().is_none());
-
-        let mut handler = GreedyTokenizer::new(&b"   3.14159)  # delicious"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::Number(3.14159),
+                token: Token::SomeThreadLast,
                 from: Position {
                     line: 1,
-                    position: 3
+                    column: 7,
+                    position: 7
                 },
                 to: Position {
                     line: 1,
-                    position: 9
+                    column: 13,
+                    position: 13
                 }
             }
         );
+
+        // anything else after the dash is just a name
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("some-x"))
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("some"))
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_clojure_style_identifiers() -> Result<(), TokenizerError> {
+        let tokens =
+            GreedyTokenizer::new(&b"(empty? x) (swap! a) (my-func *x*) (str->int) (-)"[..])?
+                .map(|token_and_span| token_and_span.map(|t| t.token))
+                .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::OpenParen,
+                Token::Identifier(String::from("empty?")),
+                Token::Identifier(String::from("x")),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Identifier(String::from("swap!")),
+                Token::Identifier(String::from("a")),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Identifier(String::from("my-func")),
+                // names have to start with a letter, so this is still multiplication
+                Token::Identifier(String::from("*")),
+                Token::Identifier(String::from("x*")),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Identifier(String::from("str->int")),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Identifier(String::from("-")),
+                Token::CloseParen,
+            ]
+        );
+
+        // namespaced names keep their dots and slashes
+        let tokens = GreedyTokenizer::new(&b"(ns app.core) (str/join xs) ns.x"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::OpenParen,
+                Token::Ns,
+                Token::Identifier(String::from("app.core")),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Identifier(String::from("str/join")),
+                Token::Identifier(String::from("xs")),
+                Token::CloseParen,
+                Token::Identifier(String::from("ns.x")),
+            ]
+        );
+
+        let mut handler = GreedyTokenizer::new(&b"empty?"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::CloseParen,
+                token: Token::Identifier(String::from("empty?")),
                 from: Position {
                     line: 1,
-                    position: 10
+                    column: 0,
+                    position: 0
                 },
                 to: Position {
                     line: 1,
-                    position: 10
+                    column: 5,
+                    position: 5
                 }
             }
         );
-        assert!(handler.next().is_none());
 
         Ok(())
     }
 
     #[test]
-    fn it_throws_error_on_bad_numeric() -> Result<(), TokenizerError> {
-        let mut handler = GreedyTokenizer::new(&b"120.0.1"[..])?;
-        if let TokenizerError::ReadError { message, from, to } =
-            handler.next().unwrap().unwrap_err()
-        {
-            assert_eq!(
-                &message,
-                &"Unable to parse number '120.0.1': invalid float literal"
-            );
-            assert_eq!(
-                from,
-                Position {
-                    line: 1,
-                    position: 0
-                }
-            );
-            assert_eq!(
-                to,
-                Position {
-                    line: 1,
-                    position: 6
-                }
-            );
-        } else {
-            panic!();
-        }
+    fn it_handles_braces() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"{:a {}}"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::OpenBrace,
+                Token::Keyword(String::from("a")),
+                Token::OpenBrace,
+                Token::CloseBrace,
+                Token::CloseBrace,
+            ]
+        );
 
-        assert!(handler.next().is_none());
+        Ok(())
+    }
 
-        let mut handler = GreedyTokenizer::new(&b"  # feckin tool \n 120.0.1"[..])?;
-        if let TokenizerError::ReadError { message, from, to } =
-            handler.next().unwrap().unwrap_err()
-        {
-            assert_eq!(
-                &message,
-                &"Unable to parse number '120.0.1': invalid float literal"
-            );
-            assert_eq!(
-                from,
-                Position {
-                    line: 2,
-                    position: 1
-                }
-            );
-            assert_eq!(
-                to,
-                Position {
-                    line: 2,
-                    position: 7
-                }
-            );
-        } else {
-            panic!();
-        }
-        assert!(handler.next().is_none());
+    #[test]
+    fn it_handles_quotes() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"'foo (quote foo) quoted"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Quote,
+                Token::Identifier(String::from("foo")),
+                Token::OpenParen,
+                Token::QuoteForm,
+                Token::Identifier(String::from("foo")),
+                Token::CloseParen,
+                Token::Identifier(String::from("quoted")),
+            ]
+        );
 
         Ok(())
     }
 
     #[test]
-    fn it_handles_reserved_keyword_tokens() -> Result<(), TokenizerError> {
-        let mut handler = GreedyTokenizer::new(&b"def"[..])?;
+    fn it_handles_keywords() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b":foo"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
-                token: Token::Def,
+                token: Token::Keyword(String::from("foo")),
                 from: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 },
                 to: Position {
                     line: 1,
-                    position: 2
+                    column: 3,
+                    position: 3
                 }
             }
         );
         assert!(handler.next().is_none());
 
-        let mut handler = GreedyTokenizer::new(&b"   fn)  # whodat"[..])?;
+        let tokens = GreedyTokenizer::new(&b"[:db/id 1]"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
         assert_eq!(
-            handler.next().unwrap()?,
-            TokenAndSpan {
-                token: Token::Fn,
+            tokens,
+            vec![
+                Token::OpenBracket,
+                Token::Keyword(String::from("db/id")),
+                Token::Number(1.0),
+                Token::CloseBracket,
+            ]
+        );
+
+        let mut handler = GreedyTokenizer::new(&b": x"[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Keyword is missing a name after ':'"),
                 from: Position {
                     line: 1,
-                    position: 3
+                    column: 0,
+                    position: 0
                 },
                 to: Position {
                     line: 1,
-                    position: 4
+                    column: 0,
+                    position: 0
                 }
             }
         );
         assert_eq!(
-            handler.next().unwrap()?,
-            TokenAndSpan {
-                token: Token::CloseParen,
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("x"))
+        );
+
+        // a namespaced keyword needs both halves
+        let mut handler = GreedyTokenizer::new(&b":foo/ :/bar"[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Keyword ':foo/' can't start or end with '/'"),
                 from: Position {
                     line: 1,
-                    position: 5
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Keyword ':/bar' can't start or end with '/'"),
+                from: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                },
+                to: Position {
+                    line: 1,
+                    column: 10,
+                    position: 10
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_brackets() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"[1 [x]]"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::OpenBracket,
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                }
+            }
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::Number(1.0));
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenBracket);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("x"))
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::CloseBracket);
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::CloseBracket,
+                from: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                },
+                to: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_multiple_parens() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"(())"[..])?;
+
+        // two open parens
+        for position in 0..2 {
+            assert_eq!(
+                handler.next().unwrap()?,
+                TokenAndSpan {
+                    token: Token::OpenParen,
+                    from: Position {
+                        line: 1,
+                        column: position,
+                        position
+                    },
+                    to: Position {
+                        line: 1,
+                        column: position,
+                        position
+                    }
+                }
+            );
+        }
+
+        // two close parens
+        for position in 2..4 {
+            assert_eq!(
+                handler.next().unwrap()?,
+                TokenAndSpan {
+                    token: Token::CloseParen,
+                    from: Position {
+                        line: 1,
+                        column: position,
+                        position
+                    },
+                    to: Position {
+                        line: 1,
+                        column: position,
+                        position
+                    }
+                }
+            );
+        }
+
+        // an eof at the end
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_identifier_token() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"some_1dentifier"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("some_1dentifier")),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 14,
+                    position: 14
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"   w1432)  ; whodat"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("w1432")),
+                from: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                },
+                to: Position {
+                    line: 1,
+                    column: 7,
+                    position: 7
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::CloseParen,
+                from: Position {
+                    line: 1,
+                    column: 8,
+                    position: 8
+                },
+                to: Position {
+                    line: 1,
+                    column: 8,
+                    position: 8
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_ends_spans_on_the_last_consumed_char_at_line_boundaries() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"abc\nxyz\r12\n"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("abc")),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("xyz")),
+                from: Position {
+                    line: 2,
+                    column: 0,
+                    position: 4
+                },
+                to: Position {
+                    line: 2,
+                    column: 2,
+                    position: 6
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Number(12.0),
+                from: Position {
+                    line: 3,
+                    column: 0,
+                    position: 8
+                },
+                to: Position {
+                    line: 3,
+                    column: 1,
+                    position: 9
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"\nx"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("x")),
+                from: Position {
+                    line: 2,
+                    column: 0,
+                    position: 1
+                },
+                to: Position {
+                    line: 2,
+                    column: 0,
+                    position: 1
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn it_handles_numeric_token() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"120"[..])?;
+        let token_and_span = handler.next().unwrap()?;
+        assert!(token_and_span.token.approx_eq(&Token::Number(120.0)));
+        assert_eq!(
+            token_and_span.from,
+            Position {
+                line: 1,
+                column: 0,
+                position: 0
+            }
+        );
+        assert_eq!(
+            token_and_span.to,
+            Position {
+                line: 1,
+                column: 2,
+                position: 2
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"   3.14159)  ; delicious"[..])?;
+        let token_and_span = handler.next().unwrap()?;
+        assert!(token_and_span.token.approx_eq(&Token::Number(3.14159)));
+        assert_eq!(
+            token_and_span.from,
+            Position {
+                line: 1,
+                column: 3,
+                position: 3
+            }
+        );
+        assert_eq!(
+            token_and_span.to,
+            Position {
+                line: 1,
+                column: 9,
+                position: 9
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::CloseParen,
+                from: Position {
+                    line: 1,
+                    column: 10,
+                    position: 10
+                },
+                to: Position {
+                    line: 1,
+                    column: 10,
+                    position: 10
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_special_number_reads() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"##Inf (##-Inf)\n##NaN"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Number(f64::INFINITY),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Number(f64::NEG_INFINITY),
+                from: Position {
+                    line: 1,
+                    column: 7,
+                    position: 7
+                },
+                to: Position {
+                    line: 1,
+                    column: 12,
+                    position: 12
+                }
+            }
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::CloseParen);
+
+        let token_and_span = handler.next().unwrap()?;
+        match token_and_span.token {
+            Token::Number(value) => assert!(value.is_nan()),
+            other => panic!("expected NaN, got {:?}", other),
+        }
+        assert_eq!(
+            token_and_span.from,
+            Position {
+                line: 2,
+                column: 0,
+                position: 15
+            }
+        );
+        assert_eq!(
+            token_and_span.to,
+            Position {
+                line: 2,
+                column: 4,
+                position: 19
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_still_treats_spaced_hashes_as_comments() -> Result<(), TokenizerError> {
+        let mut handler =
+            GreedyTokenizer::new(&b"# Inf\n## Inf\n#Inf\nx"[..])?.with_comment_char('#');
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("x"))
+        );
+        assert!(handler.next().is_none());
+
+        // unknown special reads are an error
+        let mut handler = GreedyTokenizer::new(&b"##Infinity"[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unknown special read '##Infinity'"),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 9,
+                    position: 9
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_special_number_reads_with_full_fidelity() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"## Inf\n##Inf"[..])?
+            .with_comment_char('#')
+            .with_full_fidelity(true)
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Comment(String::from("## Inf")),
+                Token::Whitespace(String::from("\n")),
+                Token::Number(f64::INFINITY),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_throws_error_on_bad_numeric() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"120.0.1"[..])?;
+        if let TokenizerError::ReadError { message, from, to } =
+            handler.next().unwrap().unwrap_err()
+        {
+            assert_eq!(
+                &message,
+                &"Unable to parse number '120.0.1': invalid float literal"
+            );
+            assert_eq!(
+                from,
+                Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                }
+            );
+            assert_eq!(
+                to,
+                Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                }
+            );
+        } else {
+            panic!();
+        }
+
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"  ; feckin tool \n 120.0.1"[..])?;
+        if let TokenizerError::ReadError { message, from, to } =
+            handler.next().unwrap().unwrap_err()
+        {
+            assert_eq!(
+                &message,
+                &"Unable to parse number '120.0.1': invalid float literal"
+            );
+            assert_eq!(
+                from,
+                Position {
+                    line: 2,
+                    column: 1,
+                    position: 18
+                }
+            );
+            assert_eq!(
+                to,
+                Position {
+                    line: 2,
+                    column: 7,
+                    position: 24
+                }
+            );
+        } else {
+            panic!();
+        }
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_allows_leading_zeros_by_default() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"007"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::Number(7.0));
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"0"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::Number(0.0));
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_leading_zeros_in_strict_mode() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"007"[..])?.with_strict_leading_zeros(true);
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Number '007' has a superfluous leading zero"),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"0 0.5"[..])?.with_strict_leading_zeros(true);
+        assert_eq!(handler.next().unwrap()?.token, Token::Number(0.0));
+        assert!(handler
+            .next()
+            .unwrap()?
+            .token
+            .approx_eq(&Token::Number(0.5)));
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_control_chars_in_strict_mode() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"a\x01b"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("a"))
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::Unknown('\u{1}'));
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("b"))
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"a\x01b\t"[..])?.with_strict_control_chars(true);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("a"))
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Control character '\\u{1}' is not allowed"),
+                from: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                },
+                to: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("b"))
+        );
+        // tabs aren't whitespace to us, but they're still allowed through
+        assert_eq!(handler.next().unwrap()?.token, Token::Unknown('\t'));
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_string_literals() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"\"\""[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::String(String::new()),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"(println \"hello # world\nagain\")"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("println"))
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::String(String::from("hello # world\nagain")),
+                from: Position {
+                    line: 1,
+                    column: 9,
+                    position: 9
+                },
+                to: Position {
+                    line: 2,
+                    column: 5,
+                    position: 29
+                }
+            }
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::CloseParen);
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_string_escapes() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&br#""a\nb\tc\"d\\e""#[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::String(String::from("a\nb\tc\"d\\e")),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 14,
+                    position: 14
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        // unknown escapes error, and we pick up again after the string
+        let mut handler = GreedyTokenizer::new(&br#""a\qb" c"#[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unknown escape sequence '\\q'"),
+                from: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                },
+                to: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("c"))
+        );
+        assert!(handler.next().is_none());
+
+        // \u takes four hex digits, in either case
+        let mut handler = GreedyTokenizer::new(&br#""caf\u00e9 \u00C9""#[..])?;
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::String(String::from("café É"))
+        );
+        assert!(handler.next().is_none());
+
+        // anything else is an error at the escape, and we still pick up after the string
+        let mut handler = GreedyTokenizer::new(&br#""a\u00g9" "\u12" c"#[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unicode escape needs four hex digits"),
+                from: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                },
+                to: Position {
+                    line: 1,
+                    column: 5,
+                    position: 5
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unicode escape needs four hex digits"),
+                from: Position {
+                    line: 1,
+                    column: 11,
+                    position: 11
+                },
+                to: Position {
+                    line: 1,
+                    column: 14,
+                    position: 14
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("c"))
+        );
+
+        // surrogates aren't unicode scalars, so they can't go in a string on their own
+        let mut handler = GreedyTokenizer::new(&br#""\uD800""#[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unicode escape '\\uD800' isn't a valid code point"),
+                from: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                },
+                to: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_throws_error_on_unterminated_string() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"(a \"abc"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("a"))
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unterminated string literal"),
+                from: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                },
+                to: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                }
+            }
+        );
+
+        // the error leaves us at the end of the input
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&br#""abc\"#[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unterminated string literal"),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_multi_byte_identifiers() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new("(café λx)".as_bytes())?;
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("café")),
+                from: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("λx")),
+                from: Position {
+                    line: 1,
+                    column: 6,
+                    position: 7
+                },
+                to: Position {
+                    line: 1,
+                    column: 7,
+                    position: 9
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::CloseParen,
+                from: Position {
+                    line: 1,
+                    column: 8,
+                    position: 10
+                },
+                to: Position {
+                    line: 1,
+                    column: 8,
+                    position: 10
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_multi_byte_comments() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new("; a → b 🚀\nx".as_bytes())?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("x")),
+                from: Position {
+                    line: 2,
+                    column: 0,
+                    position: 15
+                },
+                to: Position {
+                    line: 2,
+                    column: 0,
+                    position: 15
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_throws_error_on_invalid_utf8() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"ab \xff\xfe cd \xc3("[..])?;
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("ab"))
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Invalid UTF-8 sequence [ff, fe]"),
+                from: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("cd")),
+                from: Position {
+                    line: 1,
+                    column: 6,
+                    position: 6
+                },
+                to: Position {
+                    line: 1,
+                    column: 7,
+                    position: 7
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Invalid UTF-8 sequence [c3]"),
+                from: Position {
+                    line: 1,
+                    column: 9,
+                    position: 9
+                },
+                to: Position {
+                    line: 1,
+                    column: 9,
+                    position: 9
+                }
+            }
+        );
+        assert_eq!(handler.next().unwrap()?.token, Token::OpenParen);
+        assert!(handler.next().is_none());
+
+        // a bad sequence right at the start is reported too
+        assert!(GreedyTokenizer::new(&b"\x80"[..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_the_input_around_invalid_utf8() -> Result<(), TokenizerError> {
+        let tokens = |source| {
+            GreedyTokenizer::new(source)
+                .unwrap()
+                .map(|item| item.map(|token_and_span| token_and_span.token))
+                .collect::<Vec<_>>()
+        };
+        let invalid = |bytes: &str, column| {
+            Err(TokenizerError::ReadError {
+                message: format!("Invalid UTF-8 sequence [{}]", bytes),
+                from: Position {
+                    line: 1,
+                    column,
+                    position: column,
+                },
+                to: Position {
+                    line: 1,
+                    column,
+                    position: column,
+                },
+            })
+        };
+
+        // the token before the bad bytes isn't lost
+        assert_eq!(
+            tokens(&b"a\xffb"[..]),
+            vec![
+                Ok(Token::Identifier(String::from("a"))),
+                invalid("ff", 1),
+                Ok(Token::Identifier(String::from("b")))
+            ]
+        );
+
+        // and neither is a char that cuts a sequence short
+        assert_eq!(
+            tokens(&b"a\xc3(b"[..]),
+            vec![
+                Ok(Token::Identifier(String::from("a"))),
+                invalid("c3", 1),
+                Ok(Token::OpenParen),
+                Ok(Token::Identifier(String::from("b")))
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_reserved_keyword_tokens() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"def"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Def,
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"if"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::If);
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"do done"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::Do);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("done"))
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"defn define"[..])?;
+        assert_eq!(handler.next().unwrap()?.token, Token::Defn);
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("define"))
+        );
+        assert!(handler.next().is_none());
+
+        let mut handler = GreedyTokenizer::new(&b"   fn)  ; whodat"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Fn,
+                from: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::CloseParen,
+                from: Position {
+                    line: 1,
+                    column: 5,
+                    position: 5
+                },
+                to: Position {
+                    line: 1,
+                    column: 5,
+                    position: 5
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_bool_tokens() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"true false"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Bool(true),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
                 },
                 to: Position {
                     line: 1,
+                    column: 3,
+                    position: 3
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Bool(false),
+                from: Position {
+                    line: 1,
+                    column: 5,
                     position: 5
+                },
+                to: Position {
+                    line: 1,
+                    column: 9,
+                    position: 9
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        // only the exact words are bools
+        let mut handler = GreedyTokenizer::new(&b"truething falsey True"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("truething"))
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("falsey"))
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("True"))
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_nil_token() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"nil nillify"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Nil,
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 2,
+                    position: 2
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?.token,
+            Token::Identifier(String::from("nillify"))
+        );
+        assert!(handler.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_exponents_and_underscores_in_numbers() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"1e10 6.022e23 1E-3 -2.5e+2 1_000 1_000.000_1"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected = [1e10, 6.022e23, 1e-3, -250.0, 1000.0, 1000.0001];
+        assert_eq!(tokens.len(), expected.len());
+        for (token, number) in tokens.iter().zip(expected) {
+            assert!(
+                token.approx_eq(&Token::Number(number)),
+                "{:?} isn't close to {}",
+                token,
+                number
+            );
+        }
+
+        let mut handler = GreedyTokenizer::new(&b"1e 2"[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unable to parse number '1e': invalid float literal"),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                }
+            }
+        );
+        assert!(handler
+            .next()
+            .unwrap()?
+            .token
+            .approx_eq(&Token::Number(2.0)));
+
+        let mut handler = GreedyTokenizer::new(&b"1_0e-"[..])?;
+        assert_eq!(
+            handler.next().unwrap().unwrap_err(),
+            TokenizerError::ReadError {
+                message: String::from("Unable to parse number '1_0e-': invalid float literal"),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
+                }
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_negative_numbers() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b"-5"[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Number(-5.0),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                }
+            }
+        );
+        assert!(handler.next().is_none());
+
+        // with a space in between, it's still subtraction
+        let tokens = GreedyTokenizer::new(&b"- 5"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![Token::Identifier(String::from("-")), Token::Number(5.0)]
+        );
+
+        let tokens = GreedyTokenizer::new(&b"(- 10 3) (def x -0.5)"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::OpenParen,
+                Token::Identifier(String::from("-")),
+                Token::Number(10.0),
+                Token::Number(3.0),
+                Token::CloseParen,
+                Token::OpenParen,
+                Token::Def,
+                Token::Identifier(String::from("x")),
+                Token::Number(-0.5),
+                Token::CloseParen,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_handles_comparison_operators() -> Result<(), TokenizerError> {
+        let mut handler = GreedyTokenizer::new(&b">= <="[..])?;
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from(">=")),
+                from: Position {
+                    line: 1,
+                    column: 0,
+                    position: 0
+                },
+                to: Position {
+                    line: 1,
+                    column: 1,
+                    position: 1
+                }
+            }
+        );
+        assert_eq!(
+            handler.next().unwrap()?,
+            TokenAndSpan {
+                token: Token::Identifier(String::from("<=")),
+                from: Position {
+                    line: 1,
+                    column: 3,
+                    position: 3
+                },
+                to: Position {
+                    line: 1,
+                    column: 4,
+                    position: 4
                 }
             }
         );
         assert!(handler.next().is_none());
 
+        let tokens = GreedyTokenizer::new(&b"(= a b) (< 1 2) (> 2 1) (not= x y)"[..])?
+            .map(|token_and_span| token_and_span.map(|t| t.token))
+            .filter(|token| {
+                !matches!(
+                    token,
+                    Ok(Token::OpenParen | Token::CloseParen | Token::Number(_))
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier(String::from("=")),
+                Token::Identifier(String::from("a")),
+                Token::Identifier(String::from("b")),
+                Token::Identifier(String::from("<")),
+                Token::Identifier(String::from(">")),
+                Token::Identifier(String::from("not=")),
+                Token::Identifier(String::from("x")),
+                Token::Identifier(String::from("y")),
+            ]
+        );
+
         Ok(())
     }
 
@@ -735,27 +2972,31 @@ mod tests {
                 token: Token::Identifier(String::from("+")),
                 from: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 },
                 to: Position {
                     line: 1,
+                    column: 0,
                     position: 0
                 }
             }
         );
         assert!(handler.next().is_none());
 
-        let mut handler = GreedyTokenizer::new(&b"   -)  # whodat"[..])?;
+        let mut handler = GreedyTokenizer::new(&b"   -)  ; whodat"[..])?;
         assert_eq!(
             handler.next().unwrap()?,
             TokenAndSpan {
                 token: Token::Identifier(String::from("-")),
                 from: Position {
                     line: 1,
+                    column: 3,
                     position: 3
                 },
                 to: Position {
                     line: 1,
+                    column: 3,
                     position: 3
                 }
             }
@@ -766,10 +3007,12 @@ mod tests {
                 token: Token::CloseParen,
                 from: Position {
                     line: 1,
+                    column: 4,
                     position: 4
                 },
                 to: Position {
                     line: 1,
+                    column: 4,
                     position: 4
                 }
             }
@@ -779,6 +3022,227 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_emits_ordered_non_overlapping_spans() -> Result<(), TokenizerError> {
+        let source = &b"(def meaning_of_life 42) ; the answer\n\n(println (+ 1 meaning_of_life))\r(fn () (x))"[..];
+        let tokens_and_spans = GreedyTokenizer::new(source)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(tokens_and_spans.len(), 21);
+
+        for token_and_span in &tokens_and_spans {
+            assert!(token_and_span.from <= token_and_span.to);
+        }
+        for pair in tokens_and_spans.windows(2) {
+            assert!(
+                pair[0].to < pair[1].from,
+                "{} overlaps {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_peeks_at_the_next_token_without_consuming_it() -> Result<(), TokenizerError> {
+        let source = &b"(f 1.2.3)"[..];
+        let expected = GreedyTokenizer::new(source)?.collect::<Vec<_>>();
+        let mut tokenizer = GreedyTokenizer::new(source)?;
+
+        let mut peeked_and_nexted = vec![];
+        while let Some(peeked) = tokenizer.peek().cloned() {
+            // peeking more than once still doesn't move along
+            assert_eq!(tokenizer.peek(), Some(&peeked));
+            assert_eq!(tokenizer.next(), Some(peeked.clone()));
+            peeked_and_nexted.push(peeked);
+        }
+
+        assert_eq!(peeked_and_nexted, expected);
+        assert_eq!(tokenizer.next(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_tokenizes_the_same_source_again_after_a_reset() -> Result<(), TokenizerError> {
+        let source = "(def π 3.14) ; pi\n(f 007 \"s\\u00e9\")";
+        let mut tokenizer = GreedyTokenizer::new(io::Cursor::new(source))?.with_full_fidelity(true);
+        let first_pass = tokenizer.by_ref().collect::<Vec<_>>();
+
+        // reset partway through too, with a token peeked
+        tokenizer.reset()?;
+        tokenizer.next();
+        tokenizer.peek();
+        tokenizer.reset()?;
+
+        assert_eq!(tokenizer.collect::<Vec<_>>(), first_pass);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_replays_tokens_after_resetting_to_a_mark() -> Result<(), TokenizerError> {
+        let source = &b"(def x (fn (a) (a)))"[..];
+        let expected = GreedyTokenizer::new(source)?.collect::<Vec<_>>();
+        let mut tokenizer = BacktrackingTokenizer::new(GreedyTokenizer::new(source)?);
+
+        // pretend we tried to read a parameter list here, and bailed once it was a def
+        let start = tokenizer.mark();
+        assert_eq!(tokenizer.next().unwrap()?.token, Token::OpenParen);
+        assert_ne!(tokenizer.next().unwrap()?.token, Token::OpenParen);
+        tokenizer.reset(start);
+
+        assert_eq!(tokenizer.collect::<Vec<_>>(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_resets_to_a_mark_in_the_middle_of_the_stream() -> Result<(), TokenizerError> {
+        let mut tokenizer = BacktrackingTokenizer::new(GreedyTokenizer::new(&b"a b 1.2.3 c"[..])?);
+
+        assert_eq!(
+            tokenizer.next().unwrap()?.token,
+            Token::Identifier(String::from("a"))
+        );
+        let middle = tokenizer.mark();
+        let rest = tokenizer.by_ref().collect::<Vec<_>>();
+        assert_eq!(rest.len(), 3);
+        assert!(rest[1].is_err());
+        assert!(tokenizer.next().is_none());
+
+        tokenizer.reset(middle);
+        assert_eq!(tokenizer.collect::<Vec<_>>(), rest);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_filters_out_tokens_by_kind() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"(@ a @ 1.2.3 b)"[..])?
+            .filter_tokens(|token| !matches!(token, Token::Unknown(_)))
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0], Ok(Token::OpenParen));
+        assert_eq!(tokens[1], Ok(Token::Identifier(String::from("a"))));
+        assert!(tokens[2].is_err());
+        assert_eq!(tokens[3], Ok(Token::Identifier(String::from("b"))));
+        assert_eq!(tokens[4], Ok(Token::CloseParen));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_skips_whitespace_and_comments_by_default() -> Result<(), TokenizerError> {
+        let tokens = GreedyTokenizer::new(&b"  a ; comment\n b"[..])?
+            .map(|item| item.map(|token_and_span| token_and_span.token))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier(String::from("a")),
+                Token::Identifier(String::from("b"))
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reproduces_the_source_from_full_fidelity_tokens() -> Result<(), TokenizerError> {
+        let source = "  (def pi 3.140) ; close enough\n\n(println (+ 1   pi))\r\n  ; bye";
+
+        let mut tokens_and_spans = vec![];
+        let mut reproduced = String::new();
+        for token_and_span in GreedyTokenizer::new(source.as_bytes())?.with_full_fidelity(true) {
+            let token_and_span = token_and_span?;
+            reproduced.push_str(&source[token_and_span.from.position..=token_and_span.to.position]);
+            tokens_and_spans.push(token_and_span);
+        }
+
+        assert_eq!(reproduced, source);
+        assert_eq!(
+            tokens_and_spans[0].token,
+            Token::Whitespace(String::from("  "))
+        );
+        assert_eq!(
+            tokens_and_spans[9].token,
+            Token::Comment(String::from("; close enough"))
+        );
+        assert_eq!(
+            tokens_and_spans[10].token,
+            Token::Whitespace(String::from("\n\n"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_compares_numbers_approximately() {
+        assert!(Token::Number(1.0).approx_eq(&Token::Number(1.0)));
+        assert!(Token::Number(1.0).approx_eq(&Token::Number(0.9999999)));
+        assert!(Token::Number(-2.5).approx_eq(&Token::Number(-2.5000001)));
+        assert!(!Token::Number(1.0).approx_eq(&Token::Number(1.001)));
+        assert!(!Token::Number(1.0).approx_eq(&Token::Number(-1.0)));
+
+        // the tolerance scales with the numbers, and infinities match themselves
+        assert!(Token::Number(6.022e23).approx_eq(&Token::Number(6.0220000001e23)));
+        assert!(!Token::Number(6.022e23).approx_eq(&Token::Number(6.023e23)));
+        assert!(!Token::Number(1e-9).approx_eq(&Token::Number(2e-9)));
+        assert!(Token::Number(f64::INFINITY).approx_eq(&Token::Number(f64::INFINITY)));
+        assert!(!Token::Number(f64::INFINITY).approx_eq(&Token::Number(f64::NEG_INFINITY)));
+        assert!(!Token::Number(f64::INFINITY).approx_eq(&Token::Number(f64::MAX)));
+        assert!(!Token::Number(f64::NAN).approx_eq(&Token::Number(f64::NAN)));
+    }
+
+    #[test]
+    fn it_compares_non_numbers_exactly() {
+        assert!(Token::OpenParen.approx_eq(&Token::OpenParen));
+        assert!(
+            Token::Identifier(String::from("a")).approx_eq(&Token::Identifier(String::from("a")))
+        );
+        assert!(
+            !Token::Identifier(String::from("a")).approx_eq(&Token::Identifier(String::from("b")))
+        );
+        assert!(!Token::OpenParen.approx_eq(&Token::CloseParen));
+        assert!(!Token::Number(1.0).approx_eq(&Token::Identifier(String::from("1"))));
+    }
+
+    #[test]
+    fn it_merges_spans() {
+        let span = |from: usize, to: usize| Span {
+            from: Position {
+                line: 1,
+                column: from,
+                position: from,
+            },
+            to: Position {
+                line: 1,
+                column: to,
+                position: to,
+            },
+        };
+
+        // overlapping, in either order
+        assert_eq!(span(0, 4).merge(&span(2, 6)), span(0, 6));
+        assert_eq!(span(2, 6).merge(&span(0, 4)), span(0, 6));
+        assert_eq!(span(0, 6).merge(&span(2, 4)), span(0, 6));
+
+        // disjoint spans cover the gap between them too
+        assert_eq!(span(0, 1).merge(&span(5, 6)), span(0, 6));
+        assert_eq!(span(5, 6).merge(&span(0, 1)), span(0, 6));
+
+        let token_and_span = TokenAndSpan {
+            token: Token::Nil,
+            from: span(3, 5).from,
+            to: span(3, 5).to,
+        };
+        assert_eq!(token_and_span.span(), span(3, 5));
+    }
+
     #[test]
     fn it_formats_token_and_span_to_string() {
         assert_eq!(
@@ -788,10 +3252,12 @@ mod tests {
                     token: Token::CloseParen,
                     from: Position {
                         line: 1,
+                        column: 1,
                         position: 1
                     },
                     to: Position {
                         line: 1,
+                        column: 1,
                         position: 1
                     }
                 }
@@ -806,10 +3272,12 @@ mod tests {
                     token: Token::Number(1.0),
                     from: Position {
                         line: 1,
+                        column: 1,
                         position: 1
                     },
                     to: Position {
                         line: 1,
+                        column: 5,
                         position: 5
                     }
                 }
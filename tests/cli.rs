@@ -0,0 +1,107 @@
+use std::fs;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust-lispy"))
+        .args(args)
+        .output()
+        .expect("failed to run rust-lispy")
+}
+
+#[test]
+fn it_summarizes_tokenize_output_on_stderr() {
+    let output = run(&["examples/print_sum.clj", "tokenize"]);
+    assert!(output.status.success());
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "OpenParen[line 1 char 0]
+\tIdentifier(\"println\")[line 1 char 1 -> line 1 char 7]
+\tOpenParen[line 1 char 9]
+\t\tIdentifier(\"+\")[line 1 char 10]
+\t\tNumber(1.0)[line 1 char 12]
+\t\tNumber(2.0)[line 1 char 14]
+\tCloseParen[line 1 char 15]
+CloseParen[line 1 char 16]
+"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "8 tokens, 1 lines, 0 errors\n"
+    );
+
+    let output = run(&["examples/declare_and_call_func.clj", "tokenize"]);
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "22 tokens, 5 lines, 0 errors\n"
+    );
+}
+
+#[test]
+fn it_prints_the_value_of_each_expression() {
+    let path = std::env::temp_dir().join("rust-lispy-eval.clj");
+    fs::write(
+        &path,
+        "(def xs [1 2.5 \"three\"])\n(defn f (x) (x))\n(f :k)\n(cond false 1)\n",
+    )
+    .unwrap();
+    let output = run(&[path.to_str().unwrap(), "eval"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[1 2.5 \"three\"]\n#<fn>\n:k\nnil\n"
+    );
+
+    // evaluating stops at the first error
+    let output = run(&["examples/print_sum.clj", "eval"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Err: UndefinedVariable(\"println\")\n"
+    );
+}
+
+#[test]
+fn it_formats_source_canonically() {
+    let output = run(&["examples/declare_and_call_func.clj", "format"]);
+    assert!(output.status.success());
+
+    let formatted = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        formatted,
+        "(def print_meaning_of_life (fn () ((println (+ 1 41)))))
+
+(print_meaning_of_life)
+"
+    );
+
+    // formatting it again doesn't change a thing
+    let path = std::env::temp_dir().join("rust-lispy-formatted.clj");
+    fs::write(&path, &formatted).unwrap();
+    let output = run(&[path.to_str().unwrap(), "format"]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), formatted);
+
+    let output = run(&["examples/use_variables.clj", "format"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        fs::read_to_string("examples/use_variables.clj").unwrap()
+    );
+}
+
+#[test]
+fn it_refuses_to_format_away_comments() {
+    let path = std::env::temp_dir().join("rust-lispy-commented.clj");
+    fs::write(&path, "(def x 1)\n\n; the answer\n(def y 42)\n").unwrap();
+    let output = run(&[path.to_str().unwrap(), "format"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Err: can't format without losing the comment on line 3\n"
+    );
+}